@@ -0,0 +1,233 @@
+//! Command line progress bar, e.g. `find . | tqdm | wc -l`
+//!
+//! - `tqdm`: Copy stdin to stdout while drawing a bar on stderr
+//! - `tqdm run`: Draw a bar from the progress a command prints, see [run]
+//! - `tqdm watch`: Draw the bars another process publishes, see [tqdm::remote]
+//!
+
+use std::io::{self, BufRead, Read, Write};
+
+use anyhow::{bail, Context, Result};
+
+#[cfg(test)]
+mod test;
+
+mod run;
+
+const USAGE: &str = "\
+Usage: tqdm [OPTIONS]
+       tqdm run --pattern <REGEX> [OPTIONS] -- <COMMAND>...
+       tqdm watch <ENDPOINT>
+
+Copy stdin to stdout while showing progress on stderr, or run a command and
+show the progress it prints, extracted by named groups `n`, `total` or `pct`,
+or show the bars published on a Unix socket or loopback `ip:port`.
+
+Options:
+      --pattern <REGEX>  Progress of the command run, e.g. '(?P<pct>\\d+)%'
+      --total <N>        Expected number of items
+      --desc <TEXT>      Description of the bar
+      --bytes            Count bytes instead of lines
+      --delim <CHAR>     Item delimiter, e.g. '\\0' [default: '\\n']
+      --unit <UNIT>      Unit of items [default: it, or B with --bytes]
+      --unit-scale       Abbreviate large numbers with SI prefixes
+      --update           Treat input items as numbers of new iterations
+      --null             Discard input instead of copying it to stdout
+  -h, --help             Print help
+  -V, --version          Print version";
+
+/// Parsed command line arguments.
+#[derive(Debug, Default)]
+struct Args {
+    total: Option<usize>,
+    desc: Option<String>,
+    bytes: bool,
+    delim: Option<u8>,
+    unit: Option<String>,
+    unit_scale: bool,
+    update: bool,
+    null: bool,
+
+    /// `run` subcommand
+    run: bool,
+    pattern: Option<String>,
+    command: Vec<String>,
+
+    /// `watch` subcommand
+    watch: Option<String>,
+}
+
+impl Args {
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut parsed = Args::default();
+
+        let mut args = args.into_iter().peekable();
+        match args.peek().map(String::as_str) {
+            Some("run") => parsed.run = args.next().is_some(),
+            Some("watch") => match args.nth(1) {
+                Some(endpoint) => parsed.watch = Some(endpoint),
+                None => bail!("watch expects an endpoint"),
+            },
+            _ => {}
+        }
+
+        while let Some(arg) = args.next() {
+            // both `--key value` and `--key=value` are accepted
+            let (key, inline) = match arg.split_once('=') {
+                Some((key, value)) if key.starts_with("--") => {
+                    (key.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg, None),
+            };
+
+            let mut value = || match inline.clone().or_else(|| args.next()) {
+                Some(value) => Ok(value),
+                None => bail!("{key} expects a value"),
+            };
+
+            match key.as_str() {
+                "--total" => parsed.total = Some(value()?.parse().context("--total")?),
+                "--desc" => parsed.desc = Some(value()?),
+                "--delim" => parsed.delim = Some(delim(&value()?)?),
+                "--unit" => parsed.unit = Some(value()?),
+                "--bytes" => parsed.bytes = true,
+                "--unit-scale" => parsed.unit_scale = true,
+                "--update" => parsed.update = true,
+                "--null" => parsed.null = true,
+                "--pattern" if parsed.run => parsed.pattern = Some(value()?),
+                "--" if parsed.run => parsed.command = args.by_ref().collect(),
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("tqdm {}", env!("CARGO_PKG_VERSION"));
+                    std::process::exit(0);
+                }
+                _ => bail!("unexpected argument '{key}'\n\n{USAGE}"),
+            }
+        }
+
+        if parsed.run && parsed.pattern.is_none() {
+            bail!("run expects --pattern <REGEX>");
+        }
+        if parsed.run && parsed.command.is_empty() {
+            bail!("run expects a command after --");
+        }
+
+        Ok(parsed)
+    }
+
+    /// Progress bar configured from the arguments.
+    fn pbar(&self) -> tqdm::Tqdm<()> {
+        let unit = match (&self.unit, self.bytes) {
+            (Some(unit), _) => unit.as_str(),
+            (None, true) => "B",
+            (None, false) => "it",
+        };
+
+        let unit_scale = match (self.bytes, self.unit_scale) {
+            (true, _) => Some(1024),
+            (false, true) => Some(1000),
+            (false, false) => None,
+        };
+
+        tqdm::pbar(self.total)
+            .desc(self.desc.as_ref())
+            .units(unit)
+            .unit_scale(unit_scale)
+    }
+}
+
+/// Single byte delimiter, with `\n`, `\r`, `\t`, `\0` and `\\` escapes.
+fn delim(s: &str) -> Result<u8> {
+    match s {
+        "\\n" => Ok(b'\n'),
+        "\\r" => Ok(b'\r'),
+        "\\t" => Ok(b'\t'),
+        "\\0" => Ok(b'\0'),
+        "\\\\" => Ok(b'\\'),
+        s if s.len() == 1 => Ok(s.as_bytes()[0]),
+        s => bail!("--delim expects a single byte, got '{s}'"),
+    }
+}
+
+fn copy(args: &Args) -> Result<()> {
+    let mut pbar = args.pbar();
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    if args.bytes {
+        let mut buf = [0; 8192];
+        loop {
+            let n = match input.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            // pass data on as it comes, e.g. `tail -f log | tqdm | grep x`
+            if !args.null && !forward(&mut output, &buf[..n])? {
+                break;
+            }
+            let _ = pbar.update(n);
+        }
+    } else {
+        let delim = args.delim.unwrap_or(b'\n');
+
+        let mut item = Vec::new();
+        while input.read_until(delim, &mut item)? > 0 {
+            if !args.null && !forward(&mut output, &item)? {
+                break;
+            }
+
+            let n = match args.update {
+                false => 1,
+                true => match String::from_utf8_lossy(item.strip_suffix(&[delim]).unwrap_or(&item))
+                    .trim()
+                {
+                    "" => 0,
+                    n => n
+                        .parse()
+                        .with_context(|| format!("--update expects numbers, got '{n}'"))?,
+                },
+            };
+            let _ = pbar.update(n);
+
+            item.clear();
+        }
+    }
+
+    // progress on stderr is best effort, never a reason to fail the copy
+    let _ = pbar.close();
+    Ok(())
+}
+
+/// Pass data on, false once the reader went away, e.g. `tqdm | head`.
+fn forward<W: Write>(output: &mut W, data: &[u8]) -> io::Result<bool> {
+    match output.write_all(data).and_then(|()| output.flush()) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn main() {
+    let result = Args::parse(std::env::args().skip(1)).and_then(|args| match &args.watch {
+        Some(endpoint) => tqdm::remote::watch(endpoint).map(|()| 0),
+        None if args.run => run::run(&args),
+        None => copy(&args).map(|()| 0),
+    });
+
+    match result {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("tqdm: {err:#}");
+            std::process::exit(1);
+        }
+    }
+}
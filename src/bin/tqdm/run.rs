@@ -0,0 +1,194 @@
+//! Progress of external commands, e.g. `rsync`, `ffmpeg` or `curl`
+//!
+//! Every line the command prints, ended by `\n` or `\r`, is matched against
+//! the pattern. Matching lines drive the bar through named groups, others
+//! are forwarded untouched to the stream they were printed on.
+//!
+//! - `n`: Current progress
+//! - `total`: Total, may change over time
+//! - `pct`: Percentage, for commands that print nothing else
+//!
+//! ```sh
+//! tqdm run --pattern '(?P<pct>\d+)%' -- curl -# -O https://example.com/file
+//! ```
+//!
+
+use std::io::{self, BufRead, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+use crate::Args;
+
+/// Values extracted from one line.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Progress {
+    pub n: Option<usize>,
+    pub total: Option<usize>,
+}
+
+/// Compile the pattern, which must capture either `n` or `pct`.
+pub(crate) fn pattern(pattern: &str) -> Result<Regex> {
+    let regex = Regex::new(pattern).context("--pattern")?;
+
+    let names: Vec<_> = regex.capture_names().flatten().collect();
+    if !names.contains(&"n") && !names.contains(&"pct") {
+        bail!("--pattern needs a (?P<n>...) or (?P<pct>...) group");
+    }
+
+    Ok(regex)
+}
+
+/// Extract progress from a line, if it matches.
+pub(crate) fn parse(regex: &Regex, line: &str) -> Option<Progress> {
+    // thousands separators as printed by e.g. rsync
+    fn number(s: &str) -> Option<f64> {
+        s.replace([',', '_'], "")
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite() && *n >= 0.)
+    }
+
+    let captures = regex.captures(line)?;
+    let group = |name| captures.name(name).and_then(|m| number(m.as_str()));
+
+    let progress = match group("pct") {
+        Some(pct) => Progress {
+            n: Some(pct.min(100.) as usize),
+            total: Some(100),
+        },
+        None => Progress {
+            n: group("n").map(|n| n as usize),
+            total: group("total").map(|total| total as usize),
+        },
+    };
+
+    Some(progress)
+}
+
+/// Call `f` on every record of `reader` ended by `\n` or `\r`, terminator included.
+pub(crate) fn records<R: BufRead, F: FnMut(&[u8]) -> io::Result<()>>(
+    mut reader: R,
+    mut f: F,
+) -> io::Result<()> {
+    let mut record = Vec::new();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            if !record.is_empty() {
+                f(&record)?;
+            }
+            return Ok(());
+        }
+
+        let (len, end) = match buf.iter().position(|&b| b == b'\n' || b == b'\r') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
+
+        record.extend_from_slice(&buf[..len]);
+        reader.consume(len);
+
+        if end {
+            f(&record)?;
+            record.clear();
+        }
+    }
+}
+
+/// Forward non-matching records of `reader` to `writer`, send the others.
+fn watch<R, W>(
+    reader: R,
+    mut writer: W,
+    regex: Regex,
+    tx: mpsc::Sender<Progress>,
+) -> thread::JoinHandle<io::Result<()>>
+where
+    R: io::Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        records(io::BufReader::new(reader), |record| {
+            let line = String::from_utf8_lossy(record);
+            match parse(&regex, line.trim_end_matches(['\r', '\n'])) {
+                // the bar may be gone, keep draining the pipe
+                Some(progress) => {
+                    let _ = tx.send(progress);
+                    Ok(())
+                }
+                // clear the bar around the record, leaving no copy of it
+                None => tqdm::write(&mut writer, record).map_err(|err| {
+                    err.downcast()
+                        .unwrap_or_else(|err| io::Error::new(io::ErrorKind::Other, err))
+                }),
+            }
+        })
+    })
+}
+
+/// Run the command, returning its exit code.
+pub(crate) fn run(args: &Args) -> Result<i32> {
+    let regex = pattern(args.pattern.as_deref().unwrap_or_default())?;
+
+    let (program, rest) = match args.command.split_first() {
+        Some(command) => command,
+        None => bail!("run expects a command after --"),
+    };
+
+    let mut child = Command::new(program)
+        .args(rest)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to run '{program}'"))?;
+
+    let (tx, rx) = mpsc::channel();
+    let watchers = [
+        child
+            .stdout
+            .take()
+            .map(|out| watch(out, io::stdout(), regex.clone(), tx.clone())),
+        child
+            .stderr
+            .take()
+            .map(|err| watch(err, io::stderr(), regex, tx)),
+    ];
+
+    let mut pbar = args.pbar();
+    let mut it = 0;
+    let mut total = args.total;
+
+    // ends once both streams are closed
+    for progress in rx {
+        if progress.total.is_some() && progress.total != total {
+            total = progress.total;
+            pbar = pbar.total(total);
+        }
+
+        // progress going backwards is ignored
+        if let Some(n) = progress.n.filter(|&n| n > it) {
+            pbar.update(n - it)?;
+            it = n;
+        }
+    }
+
+    for watcher in watchers.into_iter().flatten() {
+        if let Ok(Err(err)) = watcher.join() {
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                return Err(err.into());
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    match status.success() {
+        true => pbar.finish_with(tqdm::Outcome::Success)?,
+        false => pbar.finish_with(tqdm::Outcome::Failure)?,
+    }
+
+    Ok(status.code().unwrap_or(1))
+}
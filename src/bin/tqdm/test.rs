@@ -0,0 +1,131 @@
+use crate::*;
+
+fn args(args: &[&str]) -> Result<Args> {
+    Args::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+
+fn parse() {
+    let parsed = args(&["--total", "10", "--desc=files", "--unit-scale", "--null"]).unwrap();
+    assert_eq!(parsed.total, Some(10));
+    assert_eq!(parsed.desc.as_deref(), Some("files"));
+    assert!(parsed.unit_scale && parsed.null);
+    assert!(!parsed.bytes && !parsed.update);
+
+    let parsed = args(&["--bytes", "--unit", "bytes", "--delim", "\\0"]).unwrap();
+    assert!(parsed.bytes);
+    assert_eq!(parsed.unit.as_deref(), Some("bytes"));
+    assert_eq!(parsed.delim, Some(b'\0'));
+
+    assert!(args(&["--total"]).is_err());
+    assert!(args(&["--total", "many"]).is_err());
+    assert!(args(&["--frobnicate"]).is_err());
+}
+
+#[test]
+
+fn delimiter() {
+    assert_eq!(delim("\\n").unwrap(), b'\n');
+    assert_eq!(delim("\\t").unwrap(), b'\t');
+    assert_eq!(delim(",").unwrap(), b',');
+    assert!(delim("ab").is_err());
+    assert!(delim("").is_err());
+}
+
+#[test]
+
+fn run_args() {
+    let parsed = args(&[
+        "run",
+        "--pattern",
+        "(?P<n>\\d+)",
+        "--total=5",
+        "--",
+        "ls",
+        "-l",
+    ])
+    .unwrap();
+    assert!(parsed.run);
+    assert_eq!(parsed.pattern.as_deref(), Some("(?P<n>\\d+)"));
+    assert_eq!(parsed.total, Some(5));
+    assert_eq!(parsed.command, ["ls", "-l"]);
+
+    assert!(args(&["run", "--", "ls"]).is_err());
+    assert!(args(&["run", "--pattern", "x"]).is_err());
+    assert!(args(&["--pattern", "x"]).is_err());
+}
+
+#[test]
+
+fn watch_args() {
+    let parsed = args(&["watch", "/tmp/job.sock"]).unwrap();
+    assert_eq!(parsed.watch.as_deref(), Some("/tmp/job.sock"));
+
+    assert!(args(&["watch"]).is_err());
+    assert!(args(&["watch", "/tmp/job.sock", "--frobnicate"]).is_err());
+}
+
+#[test]
+
+fn run_pattern() {
+    use run::{parse, pattern, Progress};
+
+    assert!(pattern("(?P<total>\\d+)").is_err());
+    assert!(pattern("(?P<n>").is_err());
+
+    let rsync = pattern(r"^\s*(?P<n>[\d,]+)\s+(?P<pct>\d+)%").unwrap();
+    let progress = parse(&rsync, "    1,234,567  42%   1.18MB/s    0:00:01").unwrap();
+    assert_eq!(
+        progress,
+        Progress {
+            n: Some(42),
+            total: Some(100)
+        }
+    );
+
+    let counter = pattern(r"(?P<n>\d+)/(?P<total>\d+)").unwrap();
+    let progress = parse(&counter, "processed 7618/10000 files").unwrap();
+    assert_eq!(
+        progress,
+        Progress {
+            n: Some(7618),
+            total: Some(10000)
+        }
+    );
+    assert_eq!(parse(&counter, "starting"), None);
+}
+
+#[test]
+
+fn run_records() {
+    let mut records = Vec::new();
+    run::records(&b"10%\r20%\rdone\nrest"[..], |record| {
+        records.push(String::from_utf8_lossy(record).into_owned());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(records, ["10%\r", "20%\r", "done\n", "rest"]);
+}
+
+#[test]
+
+fn forward_broken_pipe() {
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut output = Vec::new();
+    assert!(forward(&mut output, b"item\n").unwrap());
+    assert_eq!(output, b"item\n");
+
+    assert!(!forward(&mut Closed, b"item\n").unwrap());
+}
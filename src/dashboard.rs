@@ -0,0 +1,128 @@
+//! Web dashboard, behind the `dashboard` feature
+//!
+//! Serves a page listing all visible bars, drawn like on the terminal and
+//! kept up to date through Server-Sent Events. Bind it to loopback and
+//! reach it through an SSH tunnel, e.g. `ssh -L 8080:localhost:8080 host`.
+//!
+//! - `/`: The dashboard
+//! - `/events`: Stream of bars rendered as HTML, one event per interval
+//!
+//!
+//! ## Examples
+//! ```no_run
+//! tqdm::dashboard::serve("127.0.0.1:8080").unwrap();
+//! for i in tqdm::tqdm(0..100) {
+//!     /* Your loop logic here */
+//! }
+//! ```
+//!
+
+use std::io::{self, Write};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use crate::{http, BAR};
+
+/// Serve the dashboard on a background thread, returning the bound address.
+///
+/// * `addr` address to listen on, e.g. `127.0.0.1:8080`
+///
+pub fn serve<A: ToSocketAddrs>(addr: A) -> Result<SocketAddr> {
+    http::serve(addr, |path, stream| match path {
+        "/" => http::respond(stream, "200 OK", "text/html; charset=utf-8", PAGE),
+        "/events" => events(stream),
+        _ => http::respond(stream, "404 Not Found", "text/plain", ""),
+    })
+}
+
+/// Columns of bars without a fixed width.
+const WIDTH: usize = 100;
+
+/// Time between two events.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>tqdm</title>
+<style>
+body { margin: 1em; background: #fff; color: #222; }
+#bars { font-family: monospace; white-space: pre; }
+</style>
+</head>
+<body>
+<div id="bars">Connecting...</div>
+<script>
+new EventSource("events").onmessage = function (event) {
+    document.getElementById("bars").innerHTML = event.data;
+};
+</script>
+</body>
+</html>
+"#;
+
+/// Stream bars until the browser goes away.
+fn events<W: Write>(stream: &mut W) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n"
+    )?;
+
+    loop {
+        write!(stream, "data: {}\n\n", html())?;
+        stream.flush()?;
+        thread::sleep(INTERVAL);
+    }
+}
+
+/// Render all visible bars as HTML, on a single line.
+pub(crate) fn html() -> String {
+    fn escape(s: &str) -> String {
+        let mut html = String::new();
+        for c in s.chars() {
+            match c {
+                '&' => html.push_str("&amp;"),
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '"' => html.push_str("&quot;"),
+                '\'' => html.push_str("&#39;"),
+                '\n' | '\r' => html.push(' '),
+                c => html.push(c),
+            }
+        }
+        html
+    }
+
+    let time = SystemTime::now();
+    let mut html = String::new();
+
+    if let Ok(tqdm) = BAR.lock() {
+        let mut bars: Vec<_> = tqdm.values().filter(|info| info.visible(time)).collect();
+        bars.sort_by_key(|info| info.config.position.unwrap_or(usize::MAX));
+
+        for info in bars {
+            let segments = match info.segments(time, info.config.width.unwrap_or(WIDTH)) {
+                Ok(segments) => segments,
+                Err(_) => continue,
+            };
+
+            html.push_str("<div>");
+            for segment in segments.iter().filter(|segment| !segment.text.is_empty()) {
+                match segment.ink.css() {
+                    css if css.is_empty() => html.push_str(&escape(&segment.text)),
+                    css => html.push_str(&format!(
+                        "<span style=\"{css}\">{}</span>",
+                        escape(&segment.text)
+                    )),
+                }
+            }
+            html.push_str("</div>");
+        }
+    }
+
+    html
+}
@@ -0,0 +1,34 @@
+//! Configuration overrides from `TQDM_*` environment variables
+//!
+//! - `TQDM_DISABLE`: Do not register nor draw any bar
+//! - `TQDM_NCOLS`: Fixed bar width
+//! - `TQDM_DYNAMIC_NCOLS`: Track the terminal width on every refresh
+//! - `TQDM_COLOUR` (or `TQDM_COLOR`): Bar colour, e.g. `green`
+//! - `TQDM_STYLE`: Bar style, e.g. `balloon`
+//! - `TQDM_ASCII`: Force the `ASCII` style
+//! - `TQDM_MININTERVAL`: Minimum seconds between refreshes
+//! - `TQDM_MINITERS`: Minimum iterations between refreshes
+//! - `TQDM_LEAVE`: Keep the bar after termination (opposite of `TQDM_CLEAR`)
+//! - `TQDM_CLEAR`: Remove the bar after termination
+//! - `TQDM_POSITION`: Row at which the bar is drawn
+//! - `TQDM_DELAY`: Seconds before the bar is first displayed
+//! - `TQDM_ENDPOINT`: Forward bars to the parent process serving this endpoint
+//! - `TQDM_STATE_FILE`: File keyed bars are saved to and resumed from
+//! - `TQDM_HISTORY_FILE`: File rates of past runs are learned from
+//! - `TQDM_OSC`: Report progress in the terminal title, `primary` or `aggregate`
+//!
+//! Values that cannot be parsed are ignored.
+
+use std::str::FromStr;
+
+pub(crate) fn var<T: FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok()?.trim().parse().ok()
+}
+
+pub(crate) fn flag(key: &str) -> Option<bool> {
+    match std::env::var(key).ok()?.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" | "" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,115 @@
+//! Rate priors, see [set_history_file](crate::set_history_file)
+//!
+//! Bars that succeed record their average rate and duration under their key,
+//! or their description otherwise. A new bar known to the history starts
+//! with that rate instead of none, so that its ETA is informed from the first
+//! tick, and smoothing blends it toward the live rate as updates come in.
+//!
+//! Each line of the file is a tab separated `key`, number of runs, rate in
+//! iterations per second and duration in seconds, averaged over the last
+//! runs.
+//!
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+
+use crate::{state, Info, Outcome};
+
+pub(crate) fn set(path: PathBuf) {
+    if let Ok(mut history) = HISTORY.lock() {
+        *history = Some(path);
+    }
+}
+
+/// Average rate of past runs of a bar, if any.
+pub(crate) fn rate(info: &Info) -> Option<f64> {
+    let history = HISTORY.lock().ok()?;
+    read(history.as_ref()?)
+        .remove(key(info)?)
+        .map(|record| record.rate)
+}
+
+/// Record the run of a bar that succeeded.
+pub(crate) fn record(info: &Info, t: SystemTime) {
+    let key = match key(info) {
+        Some(key) if info.outcome == Some(Outcome::Success) => key,
+        _ => return,
+    };
+
+    let duration = match t.duration_since(info.t0) {
+        Ok(duration) if info.it > 0 && !duration.is_zero() => duration.as_secs_f64(),
+        _ => return,
+    };
+
+    if let Ok(history) = HISTORY.lock() {
+        if let Some(path) = history.as_ref() {
+            let mut records = read(path);
+            let record = records.entry(key.to_owned()).or_insert(Record {
+                runs: 0,
+                rate: 0.,
+                duration: 0.,
+            });
+
+            // incremental mean, forgetting runs older than the last few
+            record.runs = (record.runs + 1).min(RUNS);
+            let weight = 1. / record.runs as f64;
+            record.rate += (info.it as f64 / duration - record.rate) * weight;
+            record.duration += (duration - record.duration) * weight;
+
+            if let Err(err) = write(path, &records) {
+                eprintln!("{err}")
+            }
+        }
+    }
+}
+
+/* --------------------------------- PRIVATE -------------------------------- */
+
+/// Number of runs averaged.
+const RUNS: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Record {
+    runs: usize,
+    rate: f64,
+    duration: f64,
+}
+
+static HISTORY: Lazy<Mutex<Option<PathBuf>>> =
+    Lazy::new(|| Mutex::new(crate::environ::var::<String>("TQDM_HISTORY_FILE").map(PathBuf::from)));
+
+/// Explicit key, or description of named bars.
+fn key(info: &Info) -> Option<&str> {
+    info.config
+        .key
+        .as_deref()
+        .or(info.config.desc.as_deref())
+        .filter(|key| !key.is_empty())
+}
+
+fn read(path: &Path) -> BTreeMap<String, Record> {
+    let positive = |value: &f64| value.is_finite() && *value > 0.;
+    state::read_table(path, |fields| match *fields {
+        [runs, rate, duration] => Some(Record {
+            runs: runs.parse().ok().filter(|&runs| runs > 0)?,
+            rate: rate.parse().ok().filter(positive)?,
+            duration: duration.parse().ok().filter(positive)?,
+        }),
+        _ => None,
+    })
+}
+
+fn write(path: &Path, records: &BTreeMap<String, Record>) -> io::Result<()> {
+    state::write_table(path, records, |record| {
+        vec![
+            record.runs.to_string(),
+            record.rate.to_string(),
+            record.duration.to_string(),
+        ]
+    })
+}
@@ -0,0 +1,78 @@
+//! Bare minimum HTTP/1.1 server for the exporters
+//!
+//! Only `GET` requests are answered, one thread per connection, and each
+//! connection serves a single request.
+//!
+
+use std::io::{self, BufRead, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+
+use anyhow::Result;
+
+/// Serve requests on a background thread, returning the bound address.
+///
+/// * `handler` called with the request path and the connection
+///
+pub(crate) fn serve<A, F>(addr: A, handler: F) -> Result<SocketAddr>
+where
+    A: ToSocketAddrs,
+    F: Fn(&str, &mut TcpStream) -> io::Result<()> + Send + Sync + Copy + 'static,
+{
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || {
+                if let Err(err) = handle(stream, handler) {
+                    // clients hanging up are none of our business
+                    match err.kind() {
+                        io::ErrorKind::BrokenPipe
+                        | io::ErrorKind::ConnectionReset
+                        | io::ErrorKind::ConnectionAborted => {}
+                        _ => eprintln!("{err}"),
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(addr)
+}
+
+fn handle<F>(mut stream: TcpStream, handler: F) -> io::Result<()>
+where
+    F: Fn(&str, &mut TcpStream) -> io::Result<()>,
+{
+    let mut reader = io::BufReader::new(stream.try_clone()?);
+
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+
+    // headers are of no interest
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    match request.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", path, _] => handler(path, &mut stream),
+        _ => respond(&mut stream, "405 Method Not Allowed", "text/plain", ""),
+    }
+}
+
+/// Write a complete response and close the connection.
+pub(crate) fn respond<W: Write>(
+    stream: &mut W,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
@@ -26,40 +26,341 @@ use once_cell::sync::Lazy;
 mod test;
 
 pub mod style;
-pub use style::{Colour, Style};
+pub use style::{Colour, ColourPolicy, Style, Theme};
 
 pub mod lib_async;
 pub use lib_async::tqdm_async;
 
+pub mod render;
+pub use render::{Outcome, Render};
+
+mod environ;
+
+mod signal;
+
+mod state;
+
+mod history;
+
+mod osc;
+
+pub mod remote;
+
+#[cfg(any(feature = "prometheus", feature = "dashboard"))]
+mod http;
+
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+
+#[cfg(feature = "statsd")]
+pub mod statsd;
+
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+
+/// Output format of progress bars.
+///
+/// - `Terminal`: Draw bars in place with cursor movements
+/// - `JsonLines`: Emit one JSON object per bar on every refresh
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    Terminal,
+    JsonLines,
+}
+
+/// Switch the output format of all bars.
+///
+/// In `JsonLines` mode, each refresh writes one line per bar to stderr,
+/// e.g. `{"event":"update","id":0,"desc":null,"n":7,"total":10,...}`, and
+/// closing a bar writes a final `"close"` event.
+///
+///
+/// ## Examples
+/// ```
+/// tqdm::set_output(tqdm::Output::JsonLines);
+/// ```
+///
+pub fn set_output(output: Output) {
+    if let Ok(mut global) = OUTPUT.lock() {
+        *global = output;
+    }
+}
+
+fn output() -> Output {
+    OUTPUT.lock().map_or(Output::Terminal, |output| *output)
+}
+
+/// Progress reported in the terminal title and taskbar.
+///
+/// - `Off`: Leave them alone
+/// - `Primary`: Reflect the topmost bar
+/// - `Aggregate`: Reflect all bars, summing their counters and totals
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Osc {
+    Off,
+    Primary,
+    Aggregate,
+}
+
+/// Report progress in the terminal title and taskbar.
+///
+/// Alongside the bars, each refresh sets the title with `OSC 0` and the
+/// taskbar progress with `OSC 9;4`, which Windows Terminal, ConEmu and
+/// WezTerm show on their tab or taskbar button. Both are restored once all
+/// bars are gone. Defaults to `TQDM_OSC`, if set to `primary` or `aggregate`.
+///
+/// Nothing is reported when stderr is not a terminal, nor when bars are
+/// forwarded to a parent process.
+///
+///
+/// ## Examples
+/// ```
+/// tqdm::set_osc(tqdm::Osc::Aggregate);
+/// ```
+///
+pub fn set_osc(osc: Osc) {
+    osc::set(osc);
+}
+
+/// Decide when bars are drawn with colours.
+///
+/// Defaults to `ColourPolicy::Auto`, which only colours bars when stderr
+/// is a terminal and neither `NO_COLOR` nor `TERM=dumb` is set.
+///
+///
+/// ## Examples
+/// ```
+/// tqdm::set_colour_policy(tqdm::ColourPolicy::Never);
+/// ```
+///
+pub fn set_colour_policy(policy: ColourPolicy) {
+    if let Ok(mut global) = COLOUR.lock() {
+        *global = policy;
+    }
+}
+
+fn colour_policy() -> ColourPolicy {
+    COLOUR.lock().map_or(ColourPolicy::Auto, |policy| *policy)
+}
+
+/// Globally enable or disable progress bars.
+///
+/// Bars created while disabled are never registered nor drawn, and
+/// updating them costs no more than a branch. Existing bars are unaffected.
+///
+///
+/// ## Examples
+/// ```
+/// tqdm::set_enabled(false);
+/// for i in tqdm::tqdm(0..100) {
+///     /* Your loop logic here */
+/// }
+/// ```
+///
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, sync::atomic::Ordering::SeqCst);
+}
+
+/// Persist progress of keyed bars to a file, to resume after a restart.
+///
+/// Bars given a [key](Tqdm::key) are saved periodically, and pick up the
+/// saved counter, total and elapsed time when created again with the same
+/// key. Bars closed as `Success` are forgotten. Defaults to the file named
+/// by `TQDM_STATE_FILE`, if any.
+///
+///
+/// ## Examples
+/// ```no_run
+/// tqdm::set_state_file("progress.tsv");
+/// for i in tqdm::tqdm(0..100).key("import") {
+///     /* Your loop logic here */
+/// }
+/// ```
+///
+pub fn set_state_file<P: Into<path::PathBuf>>(path: P) {
+    state::set(path.into());
+}
+
+/// Learn typical rates from previous runs, for an informed ETA at start.
+///
+/// Bars that succeed record their rate under their [key](Tqdm::key), or
+/// their description otherwise. New bars found in the history start with
+/// that rate, blended toward the live rate as updates come in. Defaults to
+/// the file named by `TQDM_HISTORY_FILE`, if any.
+///
+///
+/// ## Examples
+/// ```no_run
+/// tqdm::set_history_file("history.tsv");
+/// for i in tqdm::tqdm(0..100).desc(Some("import")) {
+///     /* Your loop logic here */
+/// }
+/// ```
+///
+pub fn set_history_file<P: Into<path::PathBuf>>(path: P) {
+    history::set(path.into());
+}
+
+/// Restore the terminal when the program dies mid-loop.
+///
+/// Installs a panic hook, chained before the current one, which closes the
+/// bars of the panicking thread as `Failure`, so that a panic caught later
+/// leaves other bars alone. With the `signal` feature on Unix, a
+/// `SIGINT`/`SIGTERM` handler closes all bars. Both move the cursor below
+/// the closed bars and show it again.
+///
+///
+/// ## Examples
+/// ```
+/// tqdm::install_handlers();
+/// for i in tqdm::tqdm(0..100) {
+///     /* Your loop logic here */
+/// }
+/// ```
+///
+pub fn install_handlers() {
+    static HOOK: sync::Once = sync::Once::new();
+
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Err(err) = finalize(Some(thread::current().id())) {
+                eprintln!("{err}")
+            }
+            hook(info)
+        }));
+    });
+
+    signal::interrupt();
+}
+
+/// Close the bars of a thread, or all at once, leaving the cursor below them.
+fn finalize(thread: Option<thread::ThreadId>) -> Result<()> {
+    let mut out = io::stderr();
+
+    // a panicking thread may be holding the lock already, never block then
+    let mut tqdm = match BAR.try_lock() {
+        Ok(tqdm) => tqdm,
+        Err(sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(sync::TryLockError::WouldBlock) if thread::panicking() => {
+            out.queue(cursor::Show)?;
+            return Ok(out.flush()?);
+        }
+        Err(sync::TryLockError::WouldBlock) => BAR.lock().unwrap_or_else(|p| p.into_inner()),
+    };
+
+    let time = SystemTime::now();
+    let ids: Vec<_> = tqdm
+        .iter()
+        .filter(|(_, info)| thread.map_or(true, |thread| info.thread == thread))
+        .map(|(&id, _)| id)
+        .collect();
+    let mut bars: Vec<_> = ids
+        .into_iter()
+        .filter_map(|id| tqdm.remove_entry(&id))
+        .filter_map(|(id, mut info)| {
+            info.outcome.get_or_insert(Outcome::Failure);
+            let forwarded = complete(id, &info, time);
+            (info.visible(time) && !forwarded).then(|| (id, info))
+        })
+        .collect();
+
+    if output() == Output::Terminal {
+        let rest = tqdm.iter().filter(|(_, info)| info.visible(time));
+        osc::report(&mut out, rest, time)?;
+    }
+
+    if bars.is_empty() {
+        return Ok(());
+    }
+
+    bars.sort_by_key(|(_, info)| info.config.position.unwrap_or(usize::MAX));
+
+    if output() == Output::JsonLines {
+        for (id, info) in bars {
+            writeln!(out, "{}", info.json("close", id, time)?)?;
+        }
+
+        return Ok(out.flush()?);
+    }
+
+    out.queue(cursor::MoveToColumn(0))?;
+    out.queue(terminal::Clear(terminal::ClearType::FromCursorDown))?;
+
+    for (_, info) in bars {
+        if !info.config.clear {
+            out.queue(crossterm::style::Print(info.format(time)?))?;
+            out.queue(crossterm::style::Print("\n"))?;
+        }
+    }
+
+    out.queue(cursor::Show)?;
+    Ok(out.flush()?)
+}
+
 /// Manually refresh all bars.
 pub fn refresh() -> Result<()> {
     let mut out = io::stderr();
 
     if let Ok(tqdm) = BAR.lock() {
-        let (ncols, nrows) = size();
+        let (ncols, nrows): (usize, usize) = size();
+
+        let time = SystemTime::now();
+        let mut bars: Vec<_> = tqdm.iter().filter(|(_, info)| info.visible(time)).collect();
+
+        if output() == Output::Terminal {
+            osc::report(&mut out, bars.iter().copied(), time)?;
+        }
 
-        if tqdm.is_empty() {
+        if bars.is_empty() {
             return Ok(());
         }
 
+        remote::publish("update", bars.iter().copied(), time);
+
+        state::save(bars.iter().map(|(_, info)| *info), time);
+
+        #[cfg(feature = "statsd")]
+        statsd::gauges(bars.iter().copied(), time);
+
+        if remote::forward("update", bars.iter().copied(), time) {
+            return Ok(());
+        }
+
+        if output() == Output::JsonLines {
+            for (&id, info) in bars {
+                writeln!(out, "{}", info.json("update", id, time)?)?;
+            }
+
+            return Ok(out.flush()?);
+        }
+
         out.queue(cursor::Hide)?;
         out.queue(cursor::MoveToColumn(0))?;
 
-        let time = SystemTime::now();
+        // lines drawn at another width may have wrapped, wipe the whole region
+        let prev = NCOLS.swap(ncols, sync::atomic::Ordering::SeqCst);
+        if prev != 0 && prev != ncols {
+            out.queue(terminal::Clear(terminal::ClearType::FromCursorDown))?;
+        }
+
+        bars.sort_by_key(|(_, info)| info.config.position.unwrap_or(usize::MAX));
 
-        for info in tqdm.values().take(nrows - 1) {
-            let bar = format!("{:<1$}", info.format(time)?, ncols);
+        let nbars = bars.len();
+        for (_, info) in bars.into_iter().take(nrows.saturating_sub(1)) {
+            let bar = render::fit(&info.format(time)?, ncols);
             out.queue(crossterm::style::Print(bar))?;
         }
 
-        let nbars = tqdm.len();
         if nbars >= nrows {
             out.queue(terminal::Clear(terminal::ClearType::FromCursorDown))?;
             out.queue(crossterm::style::Print(" ... (more hidden) ..."))?;
             out.queue(cursor::MoveToColumn(0))?;
         }
 
-        if let Some(rows) = num::NonZeroUsize::new(nbars - 1) {
+        if let Some(rows) = num::NonZeroUsize::new(lines(nbars, nrows) - 1) {
             out.queue(cursor::MoveUp(rows.get() as u16))?;
         }
 
@@ -69,41 +370,96 @@ pub fn refresh() -> Result<()> {
     Ok(out.flush()?)
 }
 
+/// Write above the bars, without leaving copies of them behind.
+///
+/// * `out` stream to write to, e.g. stdout or stderr
+/// * `data` bytes to write, usually ending with a newline
+///
+/// Bars drawn on the terminal are cleared before writing and drawn again
+/// after, so that log lines and bars do not get mixed up.
+///
+///
+/// ## Examples
+/// ```
+/// let mut pbar = tqdm::pbar(Some(10));
+/// pbar.update(1).unwrap();
+/// tqdm::write(&mut std::io::stdout(), b"one done\n").unwrap();
+/// ```
+///
+pub fn write<W: Write>(out: &mut W, data: &[u8]) -> Result<()> {
+    let drawn = {
+        // written under the lock, so that no refresh gets in between
+        let tqdm = BAR.lock().unwrap_or_else(sync::PoisonError::into_inner);
+
+        let time = SystemTime::now();
+        let drawn = output() == Output::Terminal
+            && !remote::forwarding()
+            && tqdm.values().any(|info| info.visible(time));
+
+        if drawn {
+            let mut err = io::stderr();
+            err.queue(cursor::MoveToColumn(0))?;
+            err.queue(terminal::Clear(terminal::ClearType::FromCursorDown))?;
+            err.flush()?;
+        }
+
+        out.write_all(data)?;
+        out.flush()?;
+        drawn
+    };
+
+    match drawn {
+        true => refresh(),
+        false => Ok(()),
+    }
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                    TQDM                                    */
 /* -------------------------------------------------------------------------- */
 
 fn create<T>(n: Option<usize>, iter: T) -> Tqdm<T> {
     let id = ID.fetch_add(1, sync::atomic::Ordering::SeqCst);
-    if let Ok(mut tqdm) = BAR.lock() {
-        tqdm.insert(
-            id,
-            Info {
-                config: Config::default(),
+    let config = Config::default();
+    let disable = config.disable || !ENABLED.load(sync::atomic::Ordering::SeqCst);
 
-                it: 0,
-                its: None,
-                total: n,
+    if !disable {
+        signal::watch();
 
-                t0: SystemTime::now(),
-                prev: time::UNIX_EPOCH,
-            },
-        );
-    }
-
-    if let Err(err) = refresh() {
-        eprintln!("{err}")
+        if let Ok(mut tqdm) = BAR.lock() {
+            tqdm.insert(
+                id,
+                Info {
+                    config,
+
+                    it: 0,
+                    its: None,
+                    total: n,
+                    outcome: None,
+                    started: false,
+                    thread: thread::current().id(),
+                    ncols: size().0,
+
+                    t0: SystemTime::now(),
+                    prev: time::UNIX_EPOCH,
+                },
+            );
+        }
     }
 
     Tqdm {
         iter,
         id,
+        disable,
+        outcome: None,
 
         next: time::UNIX_EPOCH,
         step: 0,
 
-        mininterval: Duration::from_secs_f64(1. / 24.),
-        miniters: 1,
+        mininterval: environ::var("TQDM_MININTERVAL")
+            .filter(|secs: &f64| secs.is_finite() && *secs >= 0.)
+            .map_or(Duration::from_secs_f64(1. / 24.), Duration::from_secs_f64),
+        miniters: environ::var("TQDM_MINITERS").unwrap_or(1),
     }
 }
 
@@ -152,6 +508,8 @@ pub fn pbar(total: Option<usize>) -> Tqdm<()> {
 ///
 /// - Basic Usage
 /// ```
+/// # use std::{thread, time::Duration};
+/// # use tqdm::tqdm;
 /// for _ in tqdm(0..100) {
 ///     thread::sleep(Duration::from_millis(10));
 /// }
@@ -159,6 +517,8 @@ pub fn pbar(total: Option<usize>) -> Tqdm<()> {
 ///
 /// - Composition
 /// ```
+/// # use std::{thread, time::Duration};
+/// # use tqdm::tqdm;
 /// for _ in tqdm(tqdm(0..100).take(50)) {
 ///     thread::sleep(Duration::from_millis(10));
 /// }
@@ -166,6 +526,8 @@ pub fn pbar(total: Option<usize>) -> Tqdm<()> {
 ///
 /// - Multi-threading
 /// ```
+/// # use std::{thread, time::Duration};
+/// # use tqdm::tqdm;
 /// let threads: Vec<_> = [200, 400, 100].iter().map(|its| {
 ///         std::thread::spawn(move || {
 ///             for _ in tqdm(0..*its) {
@@ -185,6 +547,12 @@ pub struct Tqdm<T> {
     /// Hash
     id: usize,
 
+    /// Unregistered
+    disable: bool,
+
+    /// Decided before close
+    outcome: Option<Outcome>,
+
     /// Next refresh time
     next: SystemTime,
 
@@ -207,7 +575,8 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).desc(Some("Bar1"))
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).desc(Some("Bar1"));
     /// ```
     ///
     pub fn desc<S: ToString>(self, desc: Option<S>) -> Self {
@@ -224,10 +593,15 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).total(Some(50))
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).total(Some(50));
     /// ```
     ///
     pub fn total(self, total: Option<usize>) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
@@ -238,6 +612,69 @@ impl<T> Tqdm<T> {
         self
     }
 
+    /// Configure progress bar's starting counter.
+    ///
+    /// * `initial` number of iterations already done, e.g. when resuming
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(50..100).total(Some(100)).initial(50);
+    /// ```
+    ///
+    pub fn initial(self, initial: usize) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.it = initial;
+            }
+        }
+
+        self
+    }
+
+    /// Identify the progress bar across runs.
+    ///
+    /// * `key` stable name of the task
+    ///
+    /// With a [state file](set_state_file), the bar resumes from the
+    /// counter, total and elapsed time saved under this key, if any.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).key("import");
+    /// ```
+    ///
+    pub fn key<S: ToString>(self, key: S) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                let key = key.to_string();
+                if let Some(saved) = state::load(&key) {
+                    let now = SystemTime::now();
+                    info.it = saved.it;
+                    info.total = saved.total.or(info.total);
+                    info.t0 = now.checked_sub(saved.elapsed).unwrap_or(now);
+                }
+                info.config.key = Some(key);
+                info.seed();
+            }
+        }
+
+        self
+    }
+
     /// Configure progress bar's width.
     ///
     /// * `width` width limitation
@@ -247,10 +684,15 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).width(Some(100))
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).width(Some(100));
     /// ```
     ///
     pub fn width(self, width: Option<usize>) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
@@ -261,6 +703,37 @@ impl<T> Tqdm<T> {
         self
     }
 
+    /// Follow terminal resizes.
+    ///
+    /// * `dynamic_ncols` width of a bar without fixed `width`
+    ///     - true: track the terminal width on every refresh
+    ///     - false: keep the terminal width at creation
+    ///
+    /// Either way, bars never overflow the terminal and are fully redrawn
+    /// when its width changes.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).dynamic_ncols(true);
+    /// ```
+    ///
+    pub fn dynamic_ncols(self, dynamic_ncols: bool) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.dynamic_ncols = dynamic_ncols;
+            }
+        }
+
+        self
+    }
+
     /// Configure progress bar's style.
     ///
     /// * `style` bar style enum
@@ -268,10 +741,15 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).style(tqdm::Style::Balloon)
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).style(tqdm::Style::Balloon);
     /// ```
     ///
     pub fn style(self, style: Style) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
@@ -288,10 +766,15 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).units("files")
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).units("files");
     /// ```
-    /// 
+    ///
     pub fn units<S: ToString>(self, units: S) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
@@ -302,26 +785,110 @@ impl<T> Tqdm<T> {
         self
     }
 
+    /// Abbreviate large numbers with prefixes.
+    ///
+    /// * `divisor` ratio between successive prefixes
+    ///     - `Some(1000)`: SI prefixes, e.g. `7.62k/10.0k`
+    ///     - `Some(1024)`: Binary prefixes, e.g. for bytes
+    ///     - `None`: Print raw numbers
+    ///
+    /// Divisors below 2 cannot scale anything and print raw numbers too.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).units("B").unit_scale(Some(1024));
+    /// ```
+    ///
+    pub fn unit_scale(self, divisor: Option<usize>) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.unit_scale = divisor.filter(|&divisor| divisor > 1);
+            }
+        }
+
+        self
+    }
+
     /// Configure progress bar's color.
     ///
     /// * `colour` bar color enum
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).colour(tqdm::Colour::Green)
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).colour(tqdm::Colour::Green);
     /// ```
     ///
     pub fn colour(self, colour: Colour) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
-                info.config.colour = colour;
+                info.config.theme.bar.colour = colour;
             }
         }
 
         self
     }
 
+    /// Configure colours and attributes of every part of the bar.
+    ///
+    /// * `theme` inks of description, percentage, bar, counter, etc.
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// use tqdm::style::{Colour, Ink, Theme};
+    /// tqdm(0..100).theme(Theme {
+    ///     desc: Ink::new(Colour::Cyan).bold(),
+    ///     complete: Some(Ink::new(Colour::Green)),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    ///
+    pub fn theme(self, theme: Theme) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.theme = theme;
+            }
+        }
+
+        self
+    }
+
+    /// Configure progress bar's postfix.
+    ///
+    /// * `postfix` additional stats shown after the rate
+    ///     - `Some(S)`: e.g. `loss=0.25`
+    ///     - `None`: No postfix
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).postfix(Some("loss=0.25"));
+    /// ```
+    ///
+    pub fn postfix<S: ToString>(self, postfix: Option<S>) -> Self {
+        self.set_postfix(postfix);
+        self
+    }
+
     /// Exponential smoothing factor.
     ///
     /// * `smoothing` weight for the current update
@@ -329,10 +896,15 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).smoothing(0.9999)
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).smoothing(0.9999);
     /// ```
     ///
     pub fn smoothing(self, smoothing: f64) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
@@ -343,6 +915,131 @@ impl<T> Tqdm<T> {
         self
     }
 
+    /// Configure progress bar's position.
+    ///
+    /// * `position` row among all bars
+    ///     - `Some(usize)`: Drawn at the given row, counting from 0
+    ///     - `None`: Drawn after positioned bars, in creation order
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).position(Some(0));
+    /// ```
+    ///
+    pub fn position(self, position: Option<usize>) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.position = position;
+            }
+        }
+
+        self
+    }
+
+    /// Disable the progress bar.
+    ///
+    /// * `disable` whether to hide this bar
+    ///     - true: unregister and erase this bar, updates become no-op
+    ///     - false: keep the bar as is
+    ///
+    /// A disabled bar cannot be enabled again.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).disable(true);
+    /// ```
+    ///
+    pub fn disable(mut self, disable: bool) -> Self {
+        if disable && !self.disable {
+            let started = BAR.lock().map_or(false, |mut tqdm| {
+                match tqdm.get_mut(&self.id) {
+                    Some(info) if info.started => {
+                        info.config.clear = true;
+                        true
+                    }
+                    // never drawn, nothing to erase
+                    _ => {
+                        tqdm.remove(&self.id);
+                        false
+                    }
+                }
+            });
+
+            if started {
+                if let Err(err) = self.close() {
+                    eprintln!("{err}")
+                }
+            }
+
+            self.disable = true;
+        }
+
+        self
+    }
+
+    /// Delay before the bar is first displayed.
+    ///
+    /// * `delay` time the bar stays hidden after creation
+    ///
+    /// A bar closed within `delay` leaves no trace on the terminal.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).delay(std::time::Duration::from_secs(1));
+    /// ```
+    ///
+    pub fn delay(self, delay: Duration) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.delay = delay;
+            }
+        }
+
+        self
+    }
+
+    /// Configure progress bar's renderer.
+    ///
+    /// * `render` object implementing [Render]
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).render(tqdm::render::Classic);
+    /// ```
+    ///
+    pub fn render<R: Render + 'static>(self, render: R) -> Self {
+        if self.disable {
+            return self;
+        }
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.render = Box::new(render);
+            }
+        }
+
+        self
+    }
+
     /// Behavior of after termination.
     ///
     /// * `clear` termination behavior
@@ -352,10 +1049,15 @@ impl<T> Tqdm<T> {
     ///
     /// ## Examples
     /// ```
-    /// tqdm(0..100).clear(true)
+    /// # use tqdm::tqdm;
+    /// tqdm(0..100).clear(true);
     /// ```
     ///
     pub fn clear(self, clear: bool) -> Self {
+        if self.disable {
+            return self;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
@@ -368,8 +1070,29 @@ impl<T> Tqdm<T> {
 }
 
 impl<T> Tqdm<T> {
+    /// Draw the bar at 0%, once configured by the builders.
+    fn start(&mut self) -> Result<()> {
+        if self.disable {
+            return Ok(());
+        }
+
+        let now = SystemTime::now();
+        if let Ok(mut tqdm) = BAR.lock() {
+            if let Some(info) = tqdm.get_mut(&self.id) {
+                info.update(now, 0);
+            }
+        }
+        self.next = now;
+
+        refresh()
+    }
+
     /// Manually update the progress bar.
     pub fn update(&mut self, n: usize) -> Result<()> {
+        if self.disable {
+            return Ok(());
+        }
+
         self.step += n;
 
         if self.step >= self.miniters {
@@ -392,50 +1115,151 @@ impl<T> Tqdm<T> {
 
     /// Set description of a progress bar.
     pub fn set_desc<S: ToString>(&self, desc: Option<S>) {
+        if self.disable {
+            return;
+        }
+
         if let Ok(mut tqdm) = BAR.lock() {
             let info = tqdm.get_mut(&self.id);
             if let Some(info) = info {
                 info.config.desc = desc.map(|desc| desc.to_string());
+                info.seed();
             }
         }
     }
 
-    /// Manually close the bar and unregister it.
-    pub fn close(&mut self) -> Result<()> {
-        let time = SystemTime::now();
-        let mut out = io::stderr();
+    /// Set postfix of a progress bar.
+    pub fn set_postfix<S: ToString>(&self, postfix: Option<S>) {
+        if self.disable {
+            return;
+        }
 
         if let Ok(mut tqdm) = BAR.lock() {
-            if let Some(mut info) = tqdm.remove(&self.id) {
-                info.update(time, self.step);
-
-                out.queue(cursor::MoveToColumn(0))?;
-
-                if info.config.clear {
-                    out.queue(cursor::MoveDown(tqdm.len() as u16))?;
-                    out.queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
-                    out.queue(cursor::MoveUp(tqdm.len() as u16))?;
-                } else {
-                    out.queue(crossterm::style::Print(info.format(time)?))?;
-                    out.queue(crossterm::style::Print("\n"))?;
-                }
+            let info = tqdm.get_mut(&self.id);
+            if let Some(info) = info {
+                info.config.postfix = postfix.map(|postfix| postfix.to_string());
             }
         }
+    }
 
-        refresh()
+    /// Close the bar with an explicit outcome.
+    ///
+    /// Without it, a bar closes as `Failure` if its thread is panicking or
+    /// it has not reached its total (unless its iterator was exhausted),
+    /// and as `Success` otherwise.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// use tqdm::{pbar, Outcome};
+    /// let mut pbar = pbar(Some(100));
+    /// pbar.update(100).unwrap();
+    /// pbar.finish_with(Outcome::Warning).unwrap();
+    /// ```
+    ///
+    pub fn finish_with(&mut self, outcome: Outcome) -> Result<()> {
+        self.outcome = Some(outcome);
+        self.close()
+    }
+
+    /// Close the bar as `Failure`, showing a message as postfix.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// let mut pbar = tqdm::pbar(Some(100));
+    /// pbar.abandon("connection lost").unwrap();
+    /// ```
+    ///
+    pub fn abandon<S: ToString>(&mut self, msg: S) -> Result<()> {
+        self.set_postfix(Some(msg));
+        self.finish_with(Outcome::Failure)
+    }
+
+    /// Manually close the bar and unregister it.
+    pub fn close(&mut self) -> Result<()> {
+        if self.disable {
+            return Ok(());
+        }
+
+        unregister(self.id, self.step, self.outcome)
     }
 }
 
+/// Remove a bar from the registry and leave its final state on screen.
+/// Report a closed bar everywhere but to stderr, true if forwarded to a parent.
+fn complete(id: usize, info: &Info, time: SystemTime) -> bool {
+    #[cfg(feature = "statsd")]
+    statsd::complete(id, info);
+
+    state::close(info, time);
+    history::record(info, time);
+
+    if !info.visible(time) {
+        return false;
+    }
+
+    remote::publish("close", [(&id, info)], time);
+    remote::forward("close", [(&id, info)], time)
+}
+
+fn unregister(id: usize, step: usize, outcome: Option<Outcome>) -> Result<()> {
+    let time = SystemTime::now();
+    let mut out = io::stderr();
+
+    if let Ok(mut tqdm) = BAR.lock() {
+        if let Some(mut info) = tqdm.remove(&id) {
+            info.update(time, step);
+            info.outcome = Some(outcome.unwrap_or_else(|| match info.total {
+                _ if thread::panicking() => Outcome::Failure,
+                Some(total) if info.it < total => Outcome::Failure,
+                _ => Outcome::Success,
+            }));
+
+            if complete(id, &info, time) || !info.visible(time) {
+                return Ok(());
+            }
+
+            if output() == Output::JsonLines {
+                writeln!(out, "{}", info.json("close", id, time)?)?;
+                return Ok(out.flush()?);
+            }
+
+            out.queue(cursor::MoveToColumn(0))?;
+
+            if info.config.clear {
+                let nbars = tqdm.values().filter(|info| info.visible(time)).count();
+                let nbars = lines(nbars, size().1);
+                out.queue(cursor::MoveDown(nbars as u16))?;
+                out.queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
+                out.queue(cursor::MoveUp(nbars as u16))?;
+            } else {
+                out.queue(crossterm::style::Print(info.format(time)?))?;
+                out.queue(crossterm::style::Print("\n"))?;
+            }
+        }
+    }
+
+    refresh()
+}
+
 impl<Iter: Iterator> Iterator for Tqdm<Iter> {
     type Item = Iter::Item;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.next == time::UNIX_EPOCH {
+            if let Err(err) = self.start() {
+                eprintln!("{err}");
+            }
+        }
+
         if let Some(next) = self.iter.next() {
             if let Err(err) = self.update(1) {
                 eprintln!("{err}");
             }
             Some(next)
         } else {
+            self.outcome.get_or_insert(Outcome::Success);
             None
         }
     }
@@ -477,7 +1301,7 @@ impl<T> Drop for Tqdm<T> {
 /// ## Examples
 /// ```
 /// use tqdm::Iter;
-/// (0..).take(1000).tqdm()
+/// (0..).take(1000).tqdm();
 /// ```
 ///
 pub trait Iter<Item>: Iterator<Item = Item> {
@@ -498,20 +1322,23 @@ impl<Iter: Iterator> crate::Iter<Iter::Item> for Iter {}
 /* --------------------------------- STATIC --------------------------------- */
 
 static ID: sync::atomic::AtomicUsize = sync::atomic::AtomicUsize::new(0);
+static NCOLS: sync::atomic::AtomicUsize = sync::atomic::AtomicUsize::new(0);
+static ENABLED: sync::atomic::AtomicBool = sync::atomic::AtomicBool::new(true);
 static BAR: Lazy<sync::Mutex<collections::BTreeMap<usize, Info>>> =
     Lazy::new(|| sync::Mutex::new(collections::BTreeMap::new()));
+static OUTPUT: Lazy<sync::Mutex<Output>> = Lazy::new(|| sync::Mutex::new(Output::Terminal));
+static COLOUR: Lazy<sync::Mutex<ColourPolicy>> = Lazy::new(|| sync::Mutex::new(ColourPolicy::Auto));
 
 fn size<T: From<u16>>() -> (T, T) {
     let (width, height) = terminal::size().unwrap_or((80, 24));
     (T::from(width), T::from(height))
 }
 
-fn ftime(seconds: usize) -> String {
-    let m = seconds / 60 % 60;
-    let s = seconds % 60;
-    match seconds / 3600 {
-        0 => format!("{m:02}:{s:02}"),
-        h => format!("{h:02}:{m:02}:{s:02}"),
+/// Number of lines taken by `nbars` bars, including the "more hidden" line.
+fn lines(nbars: usize, nrows: usize) -> usize {
+    match nbars < nrows {
+        true => nbars,
+        false => nrows,
     }
 }
 
@@ -519,24 +1346,57 @@ fn ftime(seconds: usize) -> String {
 
 struct Config {
     desc: Option<String>,
+    key: Option<String>,
     width: Option<usize>,
+    dynamic_ncols: bool,
     style: style::Style,
     units: String,
-    colour: style::Colour,
+    unit_scale: Option<usize>,
+    postfix: Option<String>,
+    theme: style::Theme,
     smoothing: f64,
     clear: bool,
+    position: Option<usize>,
+    delay: Duration,
+    disable: bool,
+    render: Box<dyn Render>,
 }
 
+/// Seeded from `TQDM_*` environment variables, see [environ].
 impl Default for Config {
     fn default() -> Self {
+        let style = match environ::flag("TQDM_ASCII") {
+            Some(true) => Some(Style::ASCII),
+            _ => environ::var("TQDM_STYLE"),
+        };
+
+        let clear =
+            environ::flag("TQDM_CLEAR").or_else(|| environ::flag("TQDM_LEAVE").map(|leave| !leave));
+
         Config {
             desc: None,
-            width: None,
-            style: Style::default(),
+            key: None,
+            width: environ::var("TQDM_NCOLS"),
+            dynamic_ncols: environ::flag("TQDM_DYNAMIC_NCOLS").unwrap_or(false),
+            style: style.unwrap_or_default(),
             units: String::from("it"),
-            colour: Colour::default(),
+            unit_scale: None,
+            postfix: None,
+            theme: Theme {
+                bar: environ::var("TQDM_COLOUR")
+                    .or_else(|| environ::var("TQDM_COLOR"))
+                    .map(style::Ink::new)
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
             smoothing: 0.3,
-            clear: false,
+            clear: clear.unwrap_or(false),
+            position: environ::var("TQDM_POSITION"),
+            delay: environ::var("TQDM_DELAY")
+                .filter(|secs: &f64| secs.is_finite() && *secs >= 0.)
+                .map_or(Duration::ZERO, Duration::from_secs_f64),
+            disable: environ::flag("TQDM_DISABLE").unwrap_or(false),
+            render: Box::new(render::Classic),
         }
     }
 }
@@ -549,81 +1409,135 @@ struct Info {
     it: usize,
     its: Option<f64>,
     total: Option<usize>,
+    outcome: Option<Outcome>,
+
+    /// Updated at least once, thus done with its builders
+    started: bool,
+
+    /// Creator, whose panic closes the bar
+    thread: thread::ThreadId,
+
+    /// Terminal width at creation
+    ncols: usize,
 
     t0: SystemTime,
     prev: SystemTime,
 }
 
 impl Info {
-    fn format(&self, t: SystemTime) -> Result<String> {
-        let desc = match &self.config.desc {
-            Some(s) => s.to_owned() + ": ",
-            None => String::new(),
-        };
+    fn snapshot(&self, t: SystemTime) -> Result<render::Snapshot<'_>> {
+        Ok(render::Snapshot {
+            desc: self.config.desc.as_deref(),
+            postfix: self.config.postfix.as_deref(),
+            units: &self.config.units,
+            unit_scale: self.config.unit_scale,
+            style: &self.config.style,
+            theme: &self.config.theme,
+            outcome: self.outcome,
+
+            it: self.it,
+            its: self.its,
+            total: self.total,
+
+            elapsed: t.duration_since(self.t0)?,
+        })
+    }
 
-        let units = self.config.units.deref();
+    fn visible(&self, t: SystemTime) -> bool {
+        self.started
+            && (self.config.delay.is_zero()
+                || t.duration_since(self.t0)
+                    .map_or(false, |elapsed| elapsed >= self.config.delay))
+    }
+
+    fn segments(&self, t: SystemTime, width: usize) -> Result<Vec<render::Segment>> {
+        let snapshot = self.snapshot(t)?;
 
-        let elapsed = ftime(t.duration_since(self.t0)?.as_secs_f64() as usize);
-        let width = self.config.width.unwrap_or_else(|| size().0);
+        // a panicking renderer must not poison the registry it is called under
+        panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            self.config.render.render(&snapshot, width)
+        }))
+        .map_err(|_| anyhow::anyhow!("renderer panicked"))
+    }
 
-        let it = self.it;
-        let its = match self.its {
-            None => String::from("?"),
-            Some(its) => format!("{its:.02}"),
+    fn format(&self, t: SystemTime) -> Result<String> {
+        self.paint(t, colour_policy().enabled())
+    }
+
+    /// Line of the bar, coloured or not regardless of the policy.
+    fn paint(&self, t: SystemTime, colour: bool) -> Result<String> {
+        let width = match (self.config.width, self.config.dynamic_ncols) {
+            (Some(width), _) => width,
+            (None, true) => size().0,
+            (None, false) => self.ncols,
         };
 
-        Ok(match self.total.filter(|&total| total >= it) {
-            None => format_args!("{desc}{it}{units} [{elapsed}, {its}{units}/s]").to_string(),
-
-            Some(total) => {
-                let pct = (it as f64 / total as f64).clamp(0.0, 1.0);
-                let eta = match self.its {
-                    None => String::from("?"),
-                    Some(its) => ftime(((total - it) as f64 / its) as usize),
-                };
-
-                let colour_code = self.config.colour.ansi_code();
-                let reset_code = if colour_code.is_empty() {
-                    ""
-                } else {
-                    Colour::reset()
-                };
-
-                let bra_ = format!("{desc}{:>3}%|", (100.0 * pct) as usize);
-                let _ket = format!("| {it}/{total} [{elapsed}<{eta}, {its}i{units}/s]");
-                let tqdm = {
-                    if let Style::Pacman = self.config.style {
-                        let limit = (width.saturating_sub(bra_.len() + _ket.len()) / 3) * 3 - 6;
-                        let pattern: Vec<_> = self.config.style.to_string().chars().collect();
-
-                        let m = pattern.len();
-                        let n = ((limit as f64 * pct) * m as f64) as usize;
-
-                        let bar = pattern.last().unwrap().to_string().repeat(n / m);
-                        let empty = " o ".repeat(limit / 3 + 2)[bar.len() + 1..].to_string();
-
-                        match n / m {
-                            x if x == limit => bar,
-                            _ => format!("{bar}{}{empty}", pattern[0]),
-                        }
-                    } else {
-                        let limit = width.saturating_sub(bra_.len() + _ket.len());
-                        let pattern: Vec<_> = self.config.style.to_string().chars().collect();
-
-                        let m = pattern.len();
-                        let n = ((limit as f64 * pct) * m as f64) as usize;
-
-                        let bar = pattern.last().unwrap().to_string().repeat(n / m);
-                        match n / m {
-                            x if x == limit => bar,
-                            _ => format!("{:<limit$}", format!("{}{}", bar, pattern[n % m])),
-                        }
-                    }
-                };
+        let mut line = String::new();
+        for segment in self.segments(t, width)? {
+            match segment.ink.ansi_code().as_str() {
+                _ if segment.text.is_empty() => {}
+                _ if !colour => line.push_str(&segment.text),
+                "" => line.push_str(&segment.text),
+                code => {
+                    line.push_str(code);
+                    line.push_str(&segment.text);
+                    line.push_str(Colour::reset());
+                }
+            }
+        }
 
-                format_args!("{bra_}{colour_code}{tqdm}{reset_code}{_ket}").to_string()
+        Ok(line)
+    }
+
+    fn json(&self, event: &str, id: usize, t: SystemTime) -> Result<String> {
+        fn string(s: &str) -> String {
+            let mut json = String::from('"');
+            for c in s.chars() {
+                match c {
+                    '"' => json.push_str("\\\""),
+                    '\\' => json.push_str("\\\\"),
+                    '\n' => json.push_str("\\n"),
+                    '\r' => json.push_str("\\r"),
+                    '\t' => json.push_str("\\t"),
+                    c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => json.push(c),
+                }
             }
-        })
+            json.push('"');
+            json
+        }
+
+        fn option<T: fmt::Display>(value: Option<T>) -> String {
+            value.map_or(String::from("null"), |value| value.to_string())
+        }
+
+        let outcome = self.outcome.map(|outcome| match outcome {
+            Outcome::Success => "success",
+            Outcome::Warning => "warning",
+            Outcome::Failure => "failure",
+        });
+
+        let snapshot = self.snapshot(t)?;
+        Ok(format!(
+            "{{\"event\":{},\"id\":{id},\"desc\":{},\"n\":{},\"total\":{},\"rate\":{},\"elapsed\":{},\"eta\":{},\"outcome\":{},\"units\":{},\"postfix\":{}}}",
+            string(event),
+            option(snapshot.desc.map(string)),
+            snapshot.it,
+            option(snapshot.total),
+            option(snapshot.its.filter(|its| its.is_finite())),
+            snapshot.elapsed.as_secs_f64(),
+            option(snapshot.eta().map(|eta| eta.as_secs_f64())),
+            option(outcome.map(string)),
+            string(snapshot.units),
+            option(snapshot.postfix.map(string)),
+        ))
+    }
+
+    /// Start from the rate of past runs, until the first measurement.
+    fn seed(&mut self) {
+        if self.prev == time::UNIX_EPOCH {
+            self.its = history::rate(self);
+        }
     }
 
     fn update(&mut self, t: SystemTime, n: usize) {
@@ -642,5 +1556,6 @@ impl Info {
 
         self.prev = t;
         self.it += n;
+        self.started = true;
     }
 }
@@ -0,0 +1,157 @@
+//! Terminal title and taskbar progress, see [set_osc](crate::set_osc)
+//!
+//! - `OSC 0`: Window title, e.g. `Downloading: 42%`
+//! - `OSC 9;4`: Taskbar progress, as understood by Windows Terminal, ConEmu
+//!   and WezTerm
+//!
+//! The previous title is pushed on the title stack when reporting starts,
+//! and popped back once all bars are gone. Sequences are only written when
+//! they change, and only when stderr is a terminal the bars are drawn on.
+//!
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crossterm::tty::IsTty;
+use once_cell::sync::Lazy;
+
+use crate::{Info, Osc, Outcome};
+
+/// Report refreshed bars, if enabled.
+pub(crate) fn report<'a, W, I>(out: &mut W, bars: I, t: SystemTime) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (&'a usize, &'a Info)>,
+{
+    // sequences would end up in a log file, or be drawn by the parent already
+    if !io::stderr().is_tty() || crate::remote::forwarding() {
+        return Ok(());
+    }
+
+    match REPORTER.lock() {
+        Ok(mut reporter) => reporter.report(out, bars, t),
+        Err(_) => Ok(()),
+    }
+}
+
+pub(crate) fn set(osc: Osc) {
+    if let Ok(mut reporter) = REPORTER.lock() {
+        reporter.osc = osc;
+    }
+}
+
+impl std::str::FromStr for Osc {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" | "" => Ok(Osc::Off),
+            "primary" => Ok(Osc::Primary),
+            "aggregate" => Ok(Osc::Aggregate),
+            _ => Err(anyhow::anyhow!("invalid osc report {s:?}")),
+        }
+    }
+}
+
+pub(crate) struct Reporter {
+    pub osc: Osc,
+
+    /// Sequences last written, if reporting
+    pub last: Option<String>,
+}
+
+impl Reporter {
+    pub fn report<'a, W, I>(&mut self, out: &mut W, bars: I, t: SystemTime) -> io::Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = (&'a usize, &'a Info)>,
+    {
+        let bars: Vec<_> = bars.into_iter().collect();
+        let sequences = match self.osc {
+            Osc::Off => None,
+            _ if bars.is_empty() => None,
+            Osc::Primary => bars
+                .iter()
+                .min_by_key(|(&id, info)| (info.config.position.unwrap_or(usize::MAX), id))
+                .and_then(|(_, info)| sequences(info.config.desc.as_deref(), &[info], t)),
+            Osc::Aggregate => match bars[..] {
+                [(_, info)] => sequences(info.config.desc.as_deref(), &[info], t),
+                _ => {
+                    let infos: Vec<_> = bars.iter().map(|(_, info)| *info).collect();
+                    sequences(Some(&format!("{} bars", infos.len())), &infos, t)
+                }
+            },
+        };
+
+        match (&self.last, sequences) {
+            (last, Some(sequences)) if last.as_ref() != Some(&sequences) => {
+                if last.is_none() {
+                    // save the title, to restore it when done
+                    write!(out, "\x1b[22;0t")?;
+                }
+                write!(out, "{sequences}")?;
+                self.last = Some(sequences);
+            }
+            (Some(_), None) => {
+                write!(out, "\x1b]9;4;0;0\x07\x1b[23;0t")?;
+                self.last = None;
+            }
+            _ => return Ok(()),
+        }
+
+        out.flush()
+    }
+}
+
+/* --------------------------------- PRIVATE -------------------------------- */
+
+static REPORTER: Lazy<Mutex<Reporter>> = Lazy::new(|| {
+    Mutex::new(Reporter {
+        osc: crate::environ::var("TQDM_OSC").unwrap_or(Osc::Off),
+        last: None,
+    })
+});
+
+/// Title and taskbar sequences of bars, summing their counters and totals.
+fn sequences(desc: Option<&str>, infos: &[&Info], t: SystemTime) -> Option<String> {
+    let mut it = 0;
+    let mut total = Some(0);
+    let mut state = 1;
+    for info in infos {
+        let bar = info.snapshot(t).ok()?;
+        it += bar.it;
+        total = total.zip(bar.total).map(|(sum, total)| sum + total);
+
+        state = match bar.outcome {
+            Some(Outcome::Failure) => 2,
+            Some(Outcome::Warning) if state != 2 => 4,
+            _ => state,
+        };
+    }
+
+    let (progress, title) = match total.filter(|&total| total > 0) {
+        Some(total) => {
+            let pct = ((it as f64 / total as f64 * 100.) as usize).min(100);
+            let title = match desc {
+                Some(desc) if !desc.is_empty() => format!("{desc}: {pct}%"),
+                _ => format!("{pct}%"),
+            };
+            (pct, title)
+        }
+        None => {
+            let units = &infos.first()?.config.units;
+            let title = match desc {
+                Some(desc) if !desc.is_empty() => format!("{desc}: {it}{units}"),
+                _ => format!("{it}{units}"),
+            };
+            // indeterminate, unless in error or warning
+            state = if state == 1 { 3 } else { state };
+            (0, title)
+        }
+    };
+
+    // OSC sequences end at the first control character
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    Some(format!("\x1b]0;{title}\x07\x1b]9;4;{state};{progress}\x07"))
+}
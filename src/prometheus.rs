@@ -0,0 +1,102 @@
+//! Prometheus exporter, behind the `prometheus` feature
+//!
+//! Every visible bar is exposed as gauges labelled by its `id` and `desc`
+//! on `/metrics`, in the Prometheus text format.
+//!
+//! - `tqdm_n`: Number of finished iterations
+//! - `tqdm_total`: Total number of iterations, if known
+//! - `tqdm_rate`: Smoothed iterations per second, once measured
+//! - `tqdm_eta_seconds`: Estimated time until completion, if known
+//!
+//! ```text
+//! tqdm_n{id="0",desc="Downloading"} 7618
+//! ```
+//!
+//!
+//! ## Examples
+//! ```no_run
+//! tqdm::prometheus::serve("127.0.0.1:9184").unwrap();
+//! for i in tqdm::tqdm(0..100) {
+//!     /* Your loop logic here */
+//! }
+//! ```
+//!
+
+use std::fmt::Write;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::SystemTime;
+
+use anyhow::Result;
+
+use crate::{http, BAR};
+
+/// Serve metrics on a background thread, returning the bound address.
+///
+/// * `addr` address to listen on, e.g. `127.0.0.1:9184`
+///
+pub fn serve<A: ToSocketAddrs>(addr: A) -> Result<SocketAddr> {
+    http::serve(addr, |path, stream| match path {
+        "/metrics" => http::respond(stream, "200 OK", "text/plain; version=0.0.4", &metrics()),
+        _ => http::respond(stream, "404 Not Found", "text/plain", ""),
+    })
+}
+
+/// Render gauges of all visible bars.
+pub(crate) fn metrics() -> String {
+    fn label(s: &str) -> String {
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    let time = SystemTime::now();
+    let mut gauges: [(&str, &str, String); 4] = [
+        ("tqdm_n", "Number of finished iterations.", String::new()),
+        ("tqdm_total", "Total number of iterations.", String::new()),
+        (
+            "tqdm_rate",
+            "Smoothed iterations per second.",
+            String::new(),
+        ),
+        (
+            "tqdm_eta_seconds",
+            "Estimated time until completion.",
+            String::new(),
+        ),
+    ];
+
+    if let Ok(tqdm) = BAR.lock() {
+        for (id, info) in tqdm.iter().filter(|(_, info)| info.visible(time)) {
+            let bar = match info.snapshot(time) {
+                Ok(bar) => bar,
+                Err(_) => continue,
+            };
+
+            let labels = format!(
+                "{{id=\"{id}\",desc=\"{}\"}}",
+                label(bar.desc.unwrap_or_default())
+            );
+            let values = [
+                Some(bar.it as f64),
+                bar.total.map(|total| total as f64),
+                bar.its.filter(|its| its.is_finite()),
+                bar.eta().map(|eta| eta.as_secs_f64()),
+            ];
+
+            for ((name, _, samples), value) in gauges.iter_mut().zip(values) {
+                if let Some(value) = value {
+                    let _ = writeln!(samples, "{name}{labels} {value}");
+                }
+            }
+        }
+    }
+
+    let mut metrics = String::new();
+    for (name, help, samples) in gauges {
+        let _ = write!(
+            metrics,
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{samples}"
+        );
+    }
+    metrics
+}
@@ -0,0 +1,570 @@
+//! Progress over sockets
+//!
+//! A process calling [serve] publishes its bars on a local endpoint, and
+//! [watch] renders the bars of such a process in another terminal, e.g.
+//! `tqdm watch /tmp/job.sock` for a job detached in tmux or under systemd.
+//!
+//! - `/path/to/socket`: Unix domain socket, on Unix only
+//! - `127.0.0.1:port`: TCP, restricted to loopback addresses
+//!
+//! The protocol is line-based: each line is a JSON object in the format of
+//! [Output::JsonLines](crate::Output::JsonLines). A client sends
+//! `{"event":"watch"}` to receive the `"update"` and `"close"` events of
+//! all bars, starting with their current state.
+//!
+//! Any other client sends such events itself, and its bars are drawn along
+//! with the ones of the server. Child processes given the [endpoint] in
+//! `TQDM_ENDPOINT` forward their bars this way instead of fighting over the
+//! terminal.
+//!
+//!
+//! ## Examples
+//! ```no_run
+//! tqdm::remote::serve("/tmp/job.sock").unwrap();
+//! for i in tqdm::tqdm(0..100) {
+//!     /* Your loop logic here */
+//! }
+//! ```
+//!
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use std::{mem, net, thread, time};
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+
+use crate::{Config, Info, Outcome, BAR, ID};
+
+/// Publish the bars of this process on a local endpoint, returning it.
+///
+/// * `endpoint` Unix socket path, or loopback `ip:port`
+///
+/// Connections are served on a background thread for the rest of the
+/// process. A stale Unix socket left by a dead process is replaced, and
+/// port 0 is replaced by the port picked by the system.
+///
+/// Child processes forward their bars here when given the [endpoint] in
+/// `TQDM_ENDPOINT`.
+///
+pub fn serve(endpoint: &str) -> Result<String> {
+    let listener = Listener::bind(endpoint)?;
+    let endpoint = listener.endpoint()?;
+
+    HOST.store(true, Ordering::SeqCst);
+    if let Ok(mut served) = ENDPOINT.lock() {
+        *served = Some(endpoint.clone());
+    }
+
+    thread::spawn(move || loop {
+        match listener.accept() {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    if let Err(err) = handle(stream) {
+                        eprintln!("{err}")
+                    }
+                });
+            }
+            Err(err) => eprintln!("{err}"),
+        }
+    });
+
+    Ok(endpoint)
+}
+
+/// Endpoint this process serves, if any.
+///
+/// Child processes forward their bars to it when given in `TQDM_ENDPOINT`,
+/// which the environment of this process is left alone for.
+///
+///
+/// ## Examples
+/// ```no_run
+/// use std::process::Command;
+/// tqdm::remote::serve("/tmp/job.sock").unwrap();
+/// Command::new("worker")
+///     .env("TQDM_ENDPOINT", tqdm::remote::endpoint().unwrap())
+///     .spawn()
+///     .unwrap();
+/// ```
+///
+pub fn endpoint() -> Option<String> {
+    ENDPOINT.lock().ok()?.clone()
+}
+
+/// Render the bars published on an endpoint until it goes away.
+///
+/// * `endpoint` Unix socket path, or loopback `ip:port`
+///
+pub fn watch(endpoint: &str) -> Result<()> {
+    let mut stream = Stream::connect(endpoint)?;
+    HOST.store(true, Ordering::SeqCst);
+    writeln!(stream, "{{\"event\":\"watch\"}}")?;
+
+    mirror(io::BufReader::new(stream))
+}
+
+/// Send events of bars to every watcher.
+pub(crate) fn publish<'a, I>(event: &str, bars: I, t: SystemTime)
+where
+    I: IntoIterator<Item = (&'a usize, &'a Info)>,
+{
+    if let Ok(mut watchers) = WATCHERS.lock() {
+        if watchers.is_empty() {
+            return;
+        }
+
+        let lines = lines(event, bars, t);
+
+        // slow or gone watchers are dropped
+        *watchers = mem::take(&mut *watchers)
+            .into_iter()
+            .filter_map(|mut stream| stream.write_all(lines.as_bytes()).ok().map(|()| stream))
+            .collect();
+    }
+}
+
+/// Send events of bars to the parent process, if any.
+///
+/// Returns false when bars should be drawn locally instead.
+pub(crate) fn forward<'a, I>(event: &str, bars: I, t: SystemTime) -> bool
+where
+    I: IntoIterator<Item = (&'a usize, &'a Info)>,
+{
+    let mut parent = match PARENT.lock() {
+        Ok(parent) => parent,
+        Err(_) => return false,
+    };
+
+    let stream = match parent.as_mut() {
+        Some(stream) => stream,
+        None => return false,
+    };
+
+    // the parent is gone, fall back to the terminal
+    if stream.write_all(lines(event, bars, t).as_bytes()).is_err() {
+        *parent = None;
+        return false;
+    }
+
+    true
+}
+
+/// Whether bars are forwarded to a parent instead of drawn.
+pub(crate) fn forwarding() -> bool {
+    PARENT.lock().map_or(false, |parent| parent.is_some())
+}
+
+fn lines<'a, I>(event: &str, bars: I, t: SystemTime) -> String
+where
+    I: IntoIterator<Item = (&'a usize, &'a Info)>,
+{
+    let mut lines = String::new();
+    for (&id, info) in bars {
+        if let Ok(line) = info.json(event, id, t) {
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+    }
+    lines
+}
+
+/// Longest a slow peer may block a write.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+/* --------------------------------- CLIENT --------------------------------- */
+
+/// Whether this process draws bars of others, which are never forwarded.
+static HOST: AtomicBool = AtomicBool::new(false);
+
+/// Endpoint this process serves, see [endpoint].
+static ENDPOINT: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Connected on first use, unless this process is a host.
+static PARENT: Lazy<Mutex<Option<Stream>>> = Lazy::new(|| {
+    let parent = match crate::environ::var::<String>("TQDM_ENDPOINT") {
+        Some(endpoint) if !HOST.load(Ordering::SeqCst) => Stream::connect(&endpoint)
+            // a parent that stops reading must not block updates
+            .and_then(|stream| {
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                Ok(stream)
+            })
+            .ok(),
+        _ => None,
+    };
+
+    Mutex::new(parent)
+});
+
+/* --------------------------------- SERVER --------------------------------- */
+
+static WATCHERS: Lazy<Mutex<Vec<Stream>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Serve one connection, subscribing it on request.
+fn handle(stream: Stream) -> Result<()> {
+    let mut reader = io::BufReader::new(stream.try_clone()?);
+
+    let mut first = String::new();
+    reader.read_line(&mut first)?;
+
+    match object(&first)
+        .as_ref()
+        .and_then(|object| object.get("event"))
+    {
+        Some(Value::String(event)) if event == "watch" => {
+            let mut stream = stream;
+            stream.set_write_timeout(Some(TIMEOUT))?;
+
+            // holding the registry, no event can slip in between
+            let tqdm = BAR.lock().map_err(|err| anyhow::anyhow!("{err}"))?;
+            let time = SystemTime::now();
+            for (&id, info) in tqdm.iter().filter(|(_, info)| info.visible(time)) {
+                writeln!(stream, "{}", info.json("update", id, time)?)?;
+            }
+
+            if let Ok(mut watchers) = WATCHERS.lock() {
+                watchers.push(stream);
+            }
+            Ok(())
+        }
+
+        _ => mirror(io::Cursor::new(first).chain(reader)),
+    }
+}
+
+/* --------------------------------- MIRROR --------------------------------- */
+
+/// Replay events read from a peer as local bars.
+fn mirror<R: BufRead>(reader: R) -> Result<()> {
+    // peer ids to local ids
+    let mut ids = BTreeMap::new();
+
+    for line in reader.lines() {
+        let event = match object(&line?).as_ref().and_then(Event::parse) {
+            Some(event) => event,
+            None => continue,
+        };
+
+        let id = *ids
+            .entry(event.id)
+            .or_insert_with(|| ID.fetch_add(1, Ordering::SeqCst));
+
+        if let Ok(mut tqdm) = BAR.lock() {
+            event.apply(tqdm.entry(id).or_insert_with(|| Info {
+                config: Config::default(),
+
+                it: 0,
+                its: None,
+                total: None,
+                outcome: None,
+                started: true,
+                thread: thread::current().id(),
+                ncols: crate::size().0,
+
+                t0: SystemTime::now(),
+                prev: time::UNIX_EPOCH,
+            }));
+        }
+
+        match event.event.as_str() {
+            "close" => {
+                ids.remove(&event.id);
+                crate::unregister(id, 0, event.outcome)?;
+            }
+            _ => crate::refresh()?,
+        }
+    }
+
+    // the peer is gone, bars it left open are closed as they are
+    for (_, id) in ids {
+        crate::unregister(id, 0, None)?;
+    }
+
+    Ok(())
+}
+
+/// One line of the protocol.
+struct Event {
+    event: String,
+    id: usize,
+    desc: Option<String>,
+    n: usize,
+    total: Option<usize>,
+    rate: Option<f64>,
+    elapsed: f64,
+    outcome: Option<Outcome>,
+    units: Option<String>,
+    postfix: Option<String>,
+}
+
+impl Event {
+    fn parse(object: &BTreeMap<String, Value>) -> Option<Self> {
+        let string = |key| match object.get(key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            _ => None,
+        };
+        let number = |key| match object.get(key) {
+            Some(Value::Number(n)) if n.is_finite() && *n >= 0. => Some(*n),
+            _ => None,
+        };
+
+        Some(Event {
+            event: string("event")?,
+            id: number("id")? as usize,
+            desc: string("desc"),
+            n: number("n").unwrap_or(0.) as usize,
+            total: number("total").map(|total| total as usize),
+            rate: number("rate"),
+            elapsed: number("elapsed").unwrap_or(0.),
+            outcome: match string("outcome").as_deref() {
+                Some("success") => Some(Outcome::Success),
+                Some("warning") => Some(Outcome::Warning),
+                Some("failure") => Some(Outcome::Failure),
+                _ => None,
+            },
+            units: string("units"),
+            postfix: string("postfix"),
+        })
+    }
+
+    fn apply(&self, info: &mut Info) {
+        let now = SystemTime::now();
+
+        info.config.desc = self.desc.clone();
+        info.config.postfix = self.postfix.clone();
+        if let Some(units) = &self.units {
+            info.config.units = units.clone();
+        }
+
+        info.it = self.n;
+        info.its = self.rate;
+        info.total = self.total;
+        info.outcome = self.outcome;
+        info.t0 = now
+            .checked_sub(Duration::from_secs_f64(self.elapsed))
+            .unwrap_or(now);
+    }
+}
+
+/* ---------------------------------- JSON ---------------------------------- */
+
+/// Values of the flat objects used by the protocol.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Number(f64),
+    String(String),
+}
+
+/// Parse a flat JSON object, as written by `Info::json`.
+pub(crate) fn object(s: &str) -> Option<BTreeMap<String, Value>> {
+    fn string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+        let mut s = String::new();
+        if chars.next()? != '"' {
+            return None;
+        }
+        loop {
+            match chars.next()? {
+                '"' => return Some(s),
+                '\\' => match chars.next()? {
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'u' => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        let code = u32::from_str_radix(&hex, 16).ok()?;
+                        s.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    }
+                    c => s.push(c),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn skip(chars: &mut std::iter::Peekable<std::str::Chars>) {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    let mut object = BTreeMap::new();
+    let mut chars = s.chars().peekable();
+
+    skip(&mut chars);
+    if chars.next()? != '{' {
+        return None;
+    }
+    skip(&mut chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Some(object);
+    }
+
+    loop {
+        skip(&mut chars);
+        let key = string(&mut chars)?;
+        skip(&mut chars);
+        if chars.next()? != ':' {
+            return None;
+        }
+        skip(&mut chars);
+
+        let value = match chars.peek()? {
+            '"' => Value::String(string(&mut chars)?),
+            'n' => {
+                let null: String = chars.by_ref().take(4).collect();
+                match null.as_str() {
+                    "null" => Value::Null,
+                    _ => return None,
+                }
+            }
+            _ => {
+                let mut number = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+                    number.push(c);
+                }
+                Value::Number(number.parse().ok()?)
+            }
+        };
+        object.insert(key, value);
+
+        skip(&mut chars);
+        match chars.next()? {
+            ',' => continue,
+            '}' => return Some(object),
+            _ => return None,
+        }
+    }
+}
+
+/* --------------------------------- SOCKET --------------------------------- */
+
+enum Stream {
+    Tcp(net::TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl Stream {
+    fn connect(endpoint: &str) -> Result<Self> {
+        if let Ok(addr) = endpoint.parse::<net::SocketAddr>() {
+            return Ok(Stream::Tcp(net::TcpStream::connect(addr)?));
+        }
+
+        #[cfg(unix)]
+        return Ok(Stream::Unix(std::os::unix::net::UnixStream::connect(
+            endpoint,
+        )?));
+
+        #[cfg(not(unix))]
+        bail!("{endpoint} is not an ip:port endpoint");
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Stream::Tcp(stream) => stream.try_clone().map(Stream::Tcp),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.try_clone().map(Stream::Unix),
+        }
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_write_timeout(timeout),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_write_timeout(timeout),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+enum Listener {
+    Tcp(net::TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl Listener {
+    fn bind(endpoint: &str) -> Result<Self> {
+        if let Ok(addr) = endpoint.parse::<net::SocketAddr>() {
+            if !addr.ip().is_loopback() {
+                bail!("{addr} is not a loopback address");
+            }
+            return Ok(Listener::Tcp(net::TcpListener::bind(addr)?));
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            use std::os::unix::net::{UnixListener, UnixStream};
+
+            let path = std::path::Path::new(endpoint);
+            if let Ok(metadata) = std::fs::symlink_metadata(path) {
+                if !metadata.file_type().is_socket() {
+                    bail!("{endpoint} already exists and is not a socket");
+                }
+
+                // nobody answering means a dead process left the socket behind
+                if UnixStream::connect(path).is_err() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+            Ok(Listener::Unix(UnixListener::bind(path)?))
+        }
+
+        #[cfg(not(unix))]
+        bail!("{endpoint} is not an ip:port endpoint");
+    }
+
+    /// Address bound, with the actual port of `ip:0`.
+    fn endpoint(&self) -> io::Result<String> {
+        match self {
+            Listener::Tcp(listener) => Ok(listener.local_addr()?.to_string()),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener
+                .local_addr()?
+                .as_pathname()
+                .and_then(std::path::Path::to_str)
+                .map(str::to_owned)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "unnamed socket")),
+        }
+    }
+
+    fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Listener::Tcp(listener) => listener.accept().map(|(stream, _)| Stream::Tcp(stream)),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener.accept().map(|(stream, _)| Stream::Unix(stream)),
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! Progress bar rendering
+//!
+//! Everything visual about a bar is produced by a [Render] implementation.
+//! It is handed a [Snapshot] of the bar together with the available width,
+//! and returns a list of [Segment]s that are concatenated into one line.
+//!
+//! - `Classic`: The default renderer, mimicking Python tqdm
+//!
+//! ## Examples
+//! ```
+//! use tqdm::render::{Render, Segment, Snapshot};
+//!
+//! struct Percent;
+//!
+//! impl Render for Percent {
+//!     fn render(&self, bar: &Snapshot, _width: usize) -> Vec<Segment> {
+//!         let pct = bar.pct().unwrap_or(0.0);
+//!         vec![Segment::plain(format!("{:.1}%", 100.0 * pct))]
+//!     }
+//! }
+//!
+//! for _ in tqdm::tqdm(0..100).render(Percent) {
+//!     /* Your loop logic here */
+//! }
+//! ```
+
+use std::time::Duration;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::style::{Colour, Ink, Style, Theme};
+
+/// How a progress bar finished.
+///
+/// - `Success`: Completed, marked with `✔`
+/// - `Warning`: Completed with caveats, marked with `⚠`
+/// - `Failure`: Abandoned, panicked or dropped before its total, marked with `✘`
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    Warning,
+    Failure,
+}
+
+impl Outcome {
+    /// Marker character, in ASCII if the locale is not UTF-8.
+    pub fn marker(&self) -> char {
+        match (self, crate::style::unicode()) {
+            (Outcome::Success, true) => '✔',
+            (Outcome::Warning, true) => '⚠',
+            (Outcome::Failure, true) => '✘',
+            (Outcome::Success, false) => 'v',
+            (Outcome::Warning, false) => '!',
+            (Outcome::Failure, false) => 'x',
+        }
+    }
+
+    /// Ink of this outcome in a theme, falling back to green, yellow and red.
+    pub fn ink(&self, theme: &Theme) -> Ink {
+        let (ink, colour) = match self {
+            Outcome::Success => (&theme.complete, Colour::Green),
+            Outcome::Warning => (&theme.warning, Colour::Yellow),
+            Outcome::Failure => (&theme.error, Colour::Red),
+        };
+
+        ink.clone().unwrap_or_else(|| Ink::new(colour))
+    }
+}
+
+/// Read-only view of a progress bar handed to [Render].
+pub struct Snapshot<'a> {
+    pub desc: Option<&'a str>,
+    pub postfix: Option<&'a str>,
+    pub units: &'a str,
+
+    /// Divisor between prefixes of abbreviated numbers, if any
+    pub unit_scale: Option<usize>,
+    pub style: &'a Style,
+    pub theme: &'a Theme,
+
+    /// `None` while running
+    pub outcome: Option<Outcome>,
+
+    /// Number of finished iterations
+    pub it: usize,
+
+    /// Smoothed iterations per second
+    pub its: Option<f64>,
+
+    /// Total number of iterations
+    pub total: Option<usize>,
+
+    /// Time since creation
+    pub elapsed: Duration,
+}
+
+impl Snapshot<'_> {
+    /// Fraction of completion in `[0, 1]`, if total is known and not exceeded.
+    pub fn pct(&self) -> Option<f64> {
+        self.total
+            .filter(|&total| total >= self.it)
+            .map(|total| (self.it as f64 / total as f64).clamp(0.0, 1.0))
+    }
+
+    /// Estimated time until completion.
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total.filter(|&total| total >= self.it)?;
+        let its = self.its.filter(|&its| its > 0.0)?;
+        let secs = (total - self.it) as f64 / its;
+        (secs < u32::MAX as f64).then(|| Duration::from_secs_f64(secs))
+    }
+}
+
+/// A piece of text printed in a single [Ink].
+#[derive(Clone, Debug)]
+pub struct Segment {
+    pub text: String,
+    pub ink: Ink,
+}
+
+impl Segment {
+    pub fn new<S: ToString, I: Into<Ink>>(text: S, ink: I) -> Self {
+        Segment {
+            text: text.to_string(),
+            ink: ink.into(),
+        }
+    }
+
+    pub fn plain<S: ToString>(text: S) -> Self {
+        Segment::new(text, Colour::None)
+    }
+}
+
+/// Trait that turns a bar [Snapshot] into printable [Segment]s.
+///
+/// * `bar` current state of the progress bar
+/// * `width` number of columns available for the whole line
+///
+pub trait Render: Send {
+    fn render(&self, bar: &Snapshot, width: usize) -> Vec<Segment>;
+}
+
+/// Default renderer mimicking Python tqdm.
+///
+/// ```text
+///  76%|███████████████▉     | 7618/10000 [00:09<00:03, 782.14it/s]
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Classic;
+
+impl Render for Classic {
+    fn render(&self, bar: &Snapshot, width: usize) -> Vec<Segment> {
+        let theme = bar.theme;
+
+        let marker = match bar.outcome {
+            Some(outcome) => Segment::new(format!("{} ", outcome.marker()), outcome.ink(theme)),
+            None => Segment::plain(""),
+        };
+
+        let postfix = match bar.postfix {
+            Some(s) => String::from(", ") + s,
+            None => String::new(),
+        };
+
+        let units = bar.units;
+
+        let elapsed = ftime(bar.elapsed.as_secs_f64() as usize);
+
+        let count = |n: usize| match bar.unit_scale {
+            Some(divisor) => fsize(n as f64, divisor as f64),
+            None => n.to_string(),
+        };
+
+        let it = count(bar.it);
+        let its = match (bar.its, bar.unit_scale) {
+            (None, _) => String::from("?"),
+            (Some(its), Some(divisor)) => fsize(its, divisor as f64),
+            (Some(its), None) => format!("{its:.02}"),
+        };
+
+        // description takes whatever is left, and is cut short with an ellipsis
+        let desc = |rest: usize| match bar.desc {
+            Some(s) if width > rest + 2 => truncate(s, width - rest - 2) + ": ",
+            _ => String::new(),
+        };
+
+        match (bar.total, bar.pct()) {
+            (Some(total), Some(pct)) => {
+                let eta = match bar.eta() {
+                    None => String::from("?"),
+                    Some(eta) => ftime(eta.as_secs() as usize),
+                };
+
+                let percentage = format!("{:>3}%", (100.0 * pct) as usize);
+                let counter = format!("{it}/{}", count(total));
+                let rate = format!("{its}{units}/s");
+
+                // invalid styles fall back to the default one
+                let fallback = Style::default();
+                let style = match bar.style.validate() {
+                    Ok(()) => bar.style,
+                    Err(_) => &fallback,
+                };
+
+                let (left, right) = (&style.left, &style.right);
+                let _ket = format!("{right} {counter} [{elapsed}<{eta}, {rate}{postfix}]");
+                let desc = desc(
+                    self::width(&marker.text)
+                        + percentage.len()
+                        + self::width(left)
+                        + MIN_BAR
+                        + self::width(&_ket),
+                );
+                let bra_ = format!("{}{desc}{percentage}{left}", marker.text);
+
+                let tqdm = style.draw(
+                    pct,
+                    width.saturating_sub(self::width(&bra_) + self::width(&_ket)),
+                );
+
+                let ink = match bar.outcome {
+                    None => &theme.bar,
+                    Some(Outcome::Success) => theme.complete.as_ref().unwrap_or(&theme.bar),
+                    Some(Outcome::Warning) => theme.warning.as_ref().unwrap_or(&theme.bar),
+                    Some(Outcome::Failure) => theme.error.as_ref().unwrap_or(&theme.bar),
+                };
+
+                vec![
+                    marker,
+                    Segment::new(desc, theme.desc.clone()),
+                    Segment::new(percentage, theme.percentage.clone()),
+                    Segment::plain(left),
+                    Segment::new(tqdm, ink.at(pct)),
+                    Segment::plain(format!("{right} ")),
+                    Segment::new(counter, theme.counter.clone()),
+                    Segment::plain(" ["),
+                    Segment::new(elapsed, theme.elapsed.clone()),
+                    Segment::plain("<"),
+                    Segment::new(eta, theme.eta.clone()),
+                    Segment::plain(", "),
+                    Segment::new(rate, theme.rate.clone()),
+                    Segment::new(postfix, theme.postfix.clone()),
+                    Segment::plain("]"),
+                ]
+            }
+
+            _ => {
+                let counter = format!("{it}{units}");
+                let rate = format!("{its}{units}/s");
+                let _ket = format!("{counter} [{elapsed}, {rate}{postfix}]");
+                let desc = desc(self::width(&marker.text) + self::width(&_ket));
+
+                vec![
+                    marker,
+                    Segment::new(desc, theme.desc.clone()),
+                    Segment::new(counter, theme.counter.clone()),
+                    Segment::plain(" ["),
+                    Segment::new(elapsed, theme.elapsed.clone()),
+                    Segment::plain(", "),
+                    Segment::new(rate, theme.rate.clone()),
+                    Segment::new(postfix, theme.postfix.clone()),
+                    Segment::plain("]"),
+                ]
+            }
+        }
+    }
+}
+
+/// Columns reserved for the bar body before truncating the description.
+const MIN_BAR: usize = 10;
+
+/// Display width of a string in terminal columns.
+///
+///
+/// ## Examples
+/// ```
+/// use tqdm::render::width;
+/// assert_eq!(width("tqdm"), 4);
+/// assert_eq!(width("进度"), 4);
+/// assert_eq!(width("▏▎▍"), 3);
+/// ```
+///
+pub fn width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Cut a string to at most `max` columns, ending with `…` if truncated.
+///
+///
+/// ## Examples
+/// ```
+/// use tqdm::render::truncate;
+/// assert_eq!(truncate("description", 20), "description");
+/// assert_eq!(truncate("description", 5), "desc…");
+/// assert_eq!(truncate("进度条", 4), "进…");
+/// ```
+///
+pub fn truncate(s: &str, max: usize) -> String {
+    if width(s) <= max {
+        return s.to_owned();
+    }
+
+    let mut cut = String::new();
+    let mut cols = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if cols + w + 1 > max {
+            break;
+        }
+        cut.push(c);
+        cols += w;
+    }
+
+    if max > 0 {
+        cut.push('…');
+    }
+    cut
+}
+
+/// Pad a string with spaces to `cols` columns.
+pub fn pad(s: &str, cols: usize) -> String {
+    format!("{s}{}", " ".repeat(cols.saturating_sub(width(s))))
+}
+
+/// Fit a line containing escape sequences to exactly `cols` columns.
+pub(crate) fn fit(line: &str, cols: usize) -> String {
+    let mut fit = String::new();
+    let mut used = 0;
+    let mut styled = false;
+
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // copy CSI sequences verbatim, they take no column
+            fit.push(c);
+            for c in chars.by_ref() {
+                fit.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            styled = true;
+            continue;
+        }
+
+        let w = c.width().unwrap_or(0);
+        if used + w > cols {
+            break;
+        }
+        fit.push(c);
+        used += w;
+    }
+
+    if styled {
+        fit.push_str(Colour::reset());
+    }
+    fit + &" ".repeat(cols - used)
+}
+
+/// Abbreviate a number to 3 significant digits, e.g. `7.62k`.
+pub(crate) fn fsize(mut n: f64, divisor: f64) -> String {
+    for prefix in ["", "k", "M", "G", "T", "P", "E", "Z"] {
+        match n.abs() {
+            a if a < 9.995 => return format!("{n:.2}{prefix}"),
+            a if a < 99.95 => return format!("{n:.1}{prefix}"),
+            a if a < 999.5 => return format!("{n:.0}{prefix}"),
+            _ => n /= divisor,
+        }
+    }
+    format!("{n:.1}Y")
+}
+
+pub(crate) fn ftime(seconds: usize) -> String {
+    let m = seconds / 60 % 60;
+    let s = seconds % 60;
+    match seconds / 3600 {
+        0 => format!("{m:02}:{s:02}"),
+        h => format!("{h:02}:{m:02}:{s:02}"),
+    }
+}
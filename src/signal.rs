@@ -0,0 +1,63 @@
+//! Signal handling, behind the `signal` feature on Unix
+//!
+//! - `SIGWINCH`: Redraw all bars as soon as the terminal is resized
+//! - `SIGINT`, `SIGTERM`: Close all bars, then die of the same signal
+//!
+
+#[cfg(all(unix, feature = "signal"))]
+pub(crate) fn watch() {
+    use signal_hook::consts::SIGWINCH;
+    use signal_hook::iterator::Signals;
+
+    static WATCH: std::sync::Once = std::sync::Once::new();
+
+    WATCH.call_once(|| {
+        let mut signals = match Signals::new([SIGWINCH]) {
+            Ok(signals) => signals,
+            Err(err) => return eprintln!("{err}"),
+        };
+
+        std::thread::spawn(move || {
+            for _ in signals.forever() {
+                if let Err(err) = crate::refresh() {
+                    eprintln!("{err}")
+                }
+            }
+        });
+    });
+}
+
+/// Without signals, resizes are picked up on the next refresh.
+#[cfg(not(all(unix, feature = "signal")))]
+pub(crate) fn watch() {}
+
+#[cfg(all(unix, feature = "signal"))]
+pub(crate) fn interrupt() {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+    use signal_hook::low_level::emulate_default_handler;
+
+    static INTERRUPT: std::sync::Once = std::sync::Once::new();
+
+    INTERRUPT.call_once(|| {
+        let mut signals = match Signals::new([SIGINT, SIGTERM]) {
+            Ok(signals) => signals,
+            Err(err) => return eprintln!("{err}"),
+        };
+
+        std::thread::spawn(move || {
+            if let Some(signal) = signals.forever().next() {
+                if let Err(err) = crate::finalize(None) {
+                    eprintln!("{err}")
+                }
+                if let Err(err) = emulate_default_handler(signal) {
+                    eprintln!("{err}")
+                }
+            }
+        });
+    });
+}
+
+/// Without signals, interrupts kill the process as usual.
+#[cfg(not(all(unix, feature = "signal")))]
+pub(crate) fn interrupt() {}
@@ -0,0 +1,226 @@
+//! Resumable progress, see [set_state_file](crate::set_state_file)
+//!
+//! Keyed bars are saved at most once per second while they refresh, and
+//! once more when closed, unless they succeeded. A bar given a key found in
+//! the file starts over from the saved counter, total and elapsed time.
+//!
+//! Each line of the file is a tab separated `key`, `it`, `total` (empty if
+//! unknown) and elapsed seconds.
+//!
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use std::{fs, io};
+
+use once_cell::sync::Lazy;
+
+use crate::{Info, Outcome};
+
+/// Progress saved by a previous run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Saved {
+    pub it: usize,
+    pub total: Option<usize>,
+    pub elapsed: Duration,
+}
+
+pub(crate) fn set(path: PathBuf) {
+    if let Ok(mut state) = STATE.lock() {
+        state.path = Some(path);
+        state.next = SystemTime::UNIX_EPOCH;
+    }
+}
+
+/// Progress saved under a key, if any.
+pub(crate) fn load(key: &str) -> Option<Saved> {
+    let state = STATE.lock().ok()?;
+    read(state.path.as_ref()?).remove(key)
+}
+
+/// Save keyed bars, at most once per interval.
+pub(crate) fn save<'a, I>(bars: I, t: SystemTime)
+where
+    I: IntoIterator<Item = &'a Info>,
+{
+    if let Ok(mut state) = STATE.lock() {
+        if t < state.next {
+            return;
+        }
+        state.next = t + INTERVAL;
+
+        if let Some(path) = &state.path {
+            let mut saved = read(path);
+            let mut dirty = false;
+            for info in bars {
+                if let Some((key, progress)) = progress(info, t) {
+                    saved.insert(key.to_owned(), progress);
+                    dirty = true;
+                }
+            }
+
+            if dirty {
+                if let Err(err) = write(path, &saved) {
+                    eprintln!("{err}")
+                }
+            }
+        }
+    }
+}
+
+/// Save a keyed bar on close, or forget it once it succeeded.
+pub(crate) fn close(info: &Info, t: SystemTime) {
+    let (key, progress) = match progress(info, t) {
+        Some(progress) => progress,
+        None => return,
+    };
+
+    if let Ok(state) = STATE.lock() {
+        if let Some(path) = &state.path {
+            let mut saved = read(path);
+            match info.outcome {
+                Some(Outcome::Success) => saved.remove(key),
+                _ => saved.insert(key.to_owned(), progress),
+            };
+
+            if let Err(err) = write(path, &saved) {
+                eprintln!("{err}")
+            }
+        }
+    }
+}
+
+/// Table keyed by the first field of each line, shared with the history.
+///
+/// Unreadable files and malformed lines are ignored.
+pub(crate) fn read_table<T, F>(path: &Path, parse: F) -> BTreeMap<String, T>
+where
+    F: Fn(&[&str]) -> Option<T>,
+{
+    let text = fs::read_to_string(path).unwrap_or_default();
+
+    let mut table = BTreeMap::new();
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        if let Some(key) = fields.next() {
+            if let Some(row) = parse(&fields.collect::<Vec<_>>()) {
+                table.insert(unescape(key), row);
+            }
+        }
+    }
+    table
+}
+
+/// Write a table back, aside then renamed so that a crash or a concurrent
+/// reader never sees half a file.
+pub(crate) fn write_table<T, F>(
+    path: &Path,
+    table: &BTreeMap<String, T>,
+    format: F,
+) -> io::Result<()>
+where
+    F: Fn(&T) -> Vec<String>,
+{
+    let mut text = String::new();
+    for (key, row) in table {
+        text += &escape(key);
+        for field in format(row) {
+            text.push('\t');
+            text += &field;
+        }
+        text.push('\n');
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(tmp, path)
+}
+
+/* --------------------------------- PRIVATE -------------------------------- */
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+struct State {
+    path: Option<PathBuf>,
+
+    /// Next save time
+    next: SystemTime,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| {
+    Mutex::new(State {
+        path: crate::environ::var::<String>("TQDM_STATE_FILE").map(PathBuf::from),
+        next: SystemTime::UNIX_EPOCH,
+    })
+});
+
+fn progress(info: &Info, t: SystemTime) -> Option<(&str, Saved)> {
+    let key = info.config.key.as_deref()?;
+    let elapsed = t.duration_since(info.t0).ok()?;
+
+    Some((
+        key,
+        Saved {
+            it: info.it,
+            total: info.total,
+            elapsed,
+        },
+    ))
+}
+
+fn read(path: &Path) -> BTreeMap<String, Saved> {
+    read_table(path, |fields| match *fields {
+        [it, total, elapsed] => Some(Saved {
+            it: it.parse().ok()?,
+            total: match total {
+                "" => None,
+                total => Some(total.parse().ok()?),
+            },
+            elapsed: Duration::from_secs_f64(
+                elapsed
+                    .parse()
+                    .ok()
+                    .filter(|secs: &f64| secs.is_finite() && *secs >= 0.)?,
+            ),
+        }),
+        _ => None,
+    })
+}
+
+fn write(path: &Path, saved: &BTreeMap<String, Saved>) -> io::Result<()> {
+    write_table(path, saved, |progress| {
+        vec![
+            progress.it.to_string(),
+            progress
+                .total
+                .map_or(String::new(), |total| total.to_string()),
+            progress.elapsed.as_secs_f64().to_string(),
+        ]
+    })
+}
+
+/// Keys are kept on a single field.
+fn escape(key: &str) -> String {
+    key.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(key: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = key.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
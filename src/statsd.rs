@@ -0,0 +1,136 @@
+//! StatsD client, behind the `statsd` feature
+//!
+//! Bars push their metrics over UDP as they refresh, thus never more often
+//! than [Tqdm::update](crate::Tqdm::update) draws them, nor than the
+//! configured interval. Metrics are named after the bar description, or
+//! `id_<id>` for anonymous bars.
+//!
+//! - `tqdm.<desc>.n`: Gauge of finished iterations
+//! - `tqdm.<desc>.rate`: Gauge of smoothed iterations per second
+//! - `tqdm.<desc>.<outcome>`: Counter of closed bars, by outcome
+//!
+//! ```text
+//! tqdm.download.n:7618|g
+//! tqdm.download.rate:782.14|g
+//! tqdm.download.success:1|c
+//! ```
+//!
+//!
+//! ## Examples
+//! ```no_run
+//! use std::time::Duration;
+//! tqdm::statsd::push("127.0.0.1:8125", Duration::from_secs(10)).unwrap();
+//! for i in tqdm::tqdm(0..100) {
+//!     /* Your loop logic here */
+//! }
+//! ```
+//!
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+
+use crate::{Info, Outcome};
+
+/// Push metrics of all bars to a StatsD server.
+///
+/// * `addr` address of the server, e.g. `127.0.0.1:8125`
+/// * `interval` minimum time between two pushes of gauges
+///
+/// Completion events are pushed as soon as bars close. Calling it again
+/// replaces the previous server.
+///
+pub fn push<A: ToSocketAddrs>(addr: A, interval: Duration) -> Result<()> {
+    let addr = match addr.to_socket_addrs()?.next() {
+        Some(addr) => addr,
+        None => bail!("no address to push to"),
+    };
+
+    let socket = match addr {
+        SocketAddr::V4(_) => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
+        SocketAddr::V6(_) => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?,
+    };
+    socket.connect(addr)?;
+
+    if let Ok(mut client) = CLIENT.lock() {
+        *client = Some(Client {
+            socket,
+            interval,
+            next: SystemTime::UNIX_EPOCH,
+        });
+    }
+
+    Ok(())
+}
+
+/// Push gauges of refreshed bars, if the interval has elapsed.
+pub(crate) fn gauges<'a, I>(bars: I, t: SystemTime)
+where
+    I: IntoIterator<Item = (&'a usize, &'a Info)>,
+{
+    if let Ok(mut client) = CLIENT.lock() {
+        if let Some(client) = client.as_mut().filter(|client| t >= client.next) {
+            client.next = t + client.interval;
+
+            for (&id, info) in bars {
+                let name = name(id, info);
+
+                let mut datagram = format!("{name}.n:{}|g", info.it);
+                if let Some(its) = info.its.filter(|its| its.is_finite()) {
+                    datagram += &format!("\n{name}.rate:{its}|g");
+                }
+
+                // UDP is fire and forget
+                let _ = client.socket.send(datagram.as_bytes());
+            }
+        }
+    }
+}
+
+/// Push final gauge and completion event of a closed bar.
+pub(crate) fn complete(id: usize, info: &Info) {
+    if let Ok(mut client) = CLIENT.lock() {
+        if let Some(client) = client.as_mut() {
+            let outcome = match info.outcome {
+                Some(Outcome::Success) | None => "success",
+                Some(Outcome::Warning) => "warning",
+                Some(Outcome::Failure) => "failure",
+            };
+
+            let name = name(id, info);
+            let datagram = format!("{name}.n:{}|g\n{name}.{outcome}:1|c", info.it);
+            let _ = client.socket.send(datagram.as_bytes());
+        }
+    }
+}
+
+/* --------------------------------- PRIVATE -------------------------------- */
+
+static CLIENT: Lazy<Mutex<Option<Client>>> = Lazy::new(|| Mutex::new(None));
+
+struct Client {
+    socket: UdpSocket,
+    interval: Duration,
+
+    /// Next push time
+    next: SystemTime,
+}
+
+/// Metric name, restricted to characters no StatsD server chokes on.
+fn name(id: usize, info: &Info) -> String {
+    let desc = match info.config.desc.as_deref() {
+        Some(desc) if !desc.is_empty() => desc
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+                _ => '_',
+            })
+            .collect(),
+        _ => format!("id_{id}"),
+    };
+
+    format!("tqdm.{desc}")
+}
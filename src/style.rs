@@ -1,14 +1,34 @@
-//! Progress bar style enumeration
+//! Progress bar styles and colours
 //!
 //! - `ASCII`: Pure ASCII bar with `"0123456789#"`
 //! - `Block`: Common bar with unicode characters `" ▏▎▍▌▋▊▉█"`
 //! - `Balloon`: Simulate balloon explosion with `".oO@*"`. Inspired by [stackoverflow](https://stackoverflow.com/a/2685509/17570263)
 //! - `Pacman`: Inspired by Arch Linux ILoveCandy
-//! - `Custom`: Create a custom progressbar style
+//! - `Arrow`: Classic `[=====>    ]`
+//! - `Pip`: Thin line like pip's download bar
+//! - `custom`: Create a custom progressbar style from a character ramp
+//!
+//! Styles can also be parsed from their lowercase name or a literal ramp,
+//! e.g. `"balloon".parse()` or `" .:#".parse()`. Unless specified, bars fall
+//! back to `ASCII` when the locale does not advertise UTF-8.
 //!
 //! Other styles are open for [contribution](https://github.com/mrlazy1708/tqdm/issues/1).
 
-#[derive(Clone, Debug)]
+use std::borrow::Cow;
+use std::iter;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Bar colour.
+///
+/// - `None`, `Red`, ..., `Cyan`: Basic ANSI colours
+/// - `Rgb`: 24-bit truecolor
+/// - `Ansi256`: Index into the 256-colour palette
+/// - `Gradient`: Interpolates red → yellow → green with completion
+///
+/// Colours the terminal cannot display are downgraded to the nearest
+/// supported one, see [Depth].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Colour {
     None,
     Red,
@@ -17,24 +37,285 @@ pub enum Colour {
     Blue,
     Magenta,
     Cyan,
+    Rgb(u8, u8, u8),
+    Ansi256(u8),
+    Gradient,
 }
 
 impl Colour {
-    pub fn ansi_code(&self) -> &str {
+    /// Escape sequence for the detected colour depth of the terminal.
+    pub fn ansi_code(&self) -> String {
+        self.code(*DEPTH)
+    }
+
+    /// Escape sequence for a given colour depth.
+    pub fn code(&self, depth: Depth) -> String {
         match self {
-            Colour::None => "",
-            Colour::Red => "\x1b[31m",
-            Colour::Green => "\x1b[32m",
-            Colour::Yellow => "\x1b[33m",
-            Colour::Blue => "\x1b[34m",
-            Colour::Magenta => "\x1b[35m",
-            Colour::Cyan => "\x1b[36m",
+            Colour::None => String::new(),
+            Colour::Red => String::from("\x1b[31m"),
+            Colour::Green => String::from("\x1b[32m"),
+            Colour::Yellow => String::from("\x1b[33m"),
+            Colour::Blue => String::from("\x1b[34m"),
+            Colour::Magenta => String::from("\x1b[35m"),
+            Colour::Cyan => String::from("\x1b[36m"),
+
+            &Colour::Rgb(r, g, b) => match depth {
+                Depth::TrueColor => format!("\x1b[38;2;{r};{g};{b}m"),
+                Depth::Ansi256 => Colour::Ansi256(ansi256(r, g, b)).code(depth),
+                Depth::Basic => format!("\x1b[{}m", 30 + basic(r, g, b)),
+            },
+
+            &Colour::Ansi256(n) => match depth {
+                Depth::TrueColor | Depth::Ansi256 => format!("\x1b[38;5;{n}m"),
+                Depth::Basic => {
+                    let (r, g, b) = rgb(n);
+                    format!("\x1b[{}m", 30 + basic(r, g, b))
+                }
+            },
+
+            Colour::Gradient => Colour::Green.code(depth),
+        }
+    }
+
+    /// Resolve `Gradient` at a given completion in `[0, 1]`.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// use tqdm::Colour;
+    /// assert_eq!(Colour::Gradient.at(0.0), Colour::Rgb(255, 0, 0));
+    /// assert_eq!(Colour::Gradient.at(0.5), Colour::Rgb(255, 255, 0));
+    /// assert_eq!(Colour::Gradient.at(1.0), Colour::Rgb(0, 255, 0));
+    /// ```
+    ///
+    pub fn at(&self, pct: f64) -> Colour {
+        match self {
+            Colour::Gradient => {
+                let pct = pct.clamp(0.0, 1.0);
+                let r = (2.0 * (1.0 - pct)).min(1.0) * 255.0;
+                let g = (2.0 * pct).min(1.0) * 255.0;
+                Colour::Rgb(r.round() as u8, g.round() as u8, 0)
+            }
+            colour => colour.clone(),
         }
     }
 
     pub fn reset() -> &'static str {
         "\x1b[0m"
     }
+
+    /// CSS colour, `None` if plain.
+    fn css(&self) -> Option<String> {
+        let (r, g, b) = match *self {
+            Colour::None => return None,
+            Colour::Red => return Some(String::from("red")),
+            Colour::Green | Colour::Gradient => return Some(String::from("green")),
+            Colour::Yellow => return Some(String::from("olive")),
+            Colour::Blue => return Some(String::from("blue")),
+            Colour::Magenta => return Some(String::from("purple")),
+            Colour::Cyan => return Some(String::from("teal")),
+            Colour::Rgb(r, g, b) => (r, g, b),
+            Colour::Ansi256(n) => rgb(n),
+        };
+        Some(format!("#{r:02x}{g:02x}{b:02x}"))
+    }
+}
+
+/// When to emit colour escape codes.
+///
+/// - `Auto`: Only when stderr is a terminal, honouring `NO_COLOR`,
+///   `CLICOLOR_FORCE` and `TERM=dumb`
+/// - `Always`: Regardless of the environment
+/// - `Never`: Plain text only
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColourPolicy {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColourPolicy {
+    /// Whether colours should be emitted under this policy.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColourPolicy::Auto => *AUTO,
+            ColourPolicy::Always => true,
+            ColourPolicy::Never => false,
+        }
+    }
+
+    /// Decide from environment variables and whether stderr is a terminal.
+    ///
+    /// `NO_COLOR` takes precedence over `CLICOLOR_FORCE`, which in turn
+    /// forces colours even when stderr is piped or `TERM` is `dumb`.
+    pub(crate) fn detect(
+        no_color: Option<&str>,
+        clicolor_force: Option<&str>,
+        term: Option<&str>,
+        tty: bool,
+    ) -> bool {
+        if no_color.map_or(false, |s| !s.is_empty()) {
+            false
+        } else if clicolor_force.map_or(false, |s| !s.is_empty() && s != "0") {
+            true
+        } else {
+            term != Some("dumb") && tty
+        }
+    }
+}
+
+impl Default for ColourPolicy {
+    fn default() -> Self {
+        ColourPolicy::Auto
+    }
+}
+
+static AUTO: once_cell::sync::Lazy<bool> = once_cell::sync::Lazy::new(|| {
+    use crossterm::tty::IsTty;
+
+    let var = |key| std::env::var(key).ok();
+    ColourPolicy::detect(
+        var("NO_COLOR").as_deref(),
+        var("CLICOLOR_FORCE").as_deref(),
+        var("TERM").as_deref(),
+        std::io::stderr().is_tty(),
+    )
+});
+
+/// Colour capability of a terminal.
+///
+/// - `Basic`: 8 standard colours
+/// - `Ansi256`: 256-colour palette, when `TERM` contains `256color`
+/// - `TrueColor`: 24-bit colours, when `COLORTERM` is `truecolor` or `24bit`
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Depth {
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+impl Depth {
+    /// Guess colour capability from environment variables.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+
+        if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || std::env::var_os("WT_SESSION").is_some()
+        {
+            Depth::TrueColor
+        } else if term.contains("256color") {
+            Depth::Ansi256
+        } else {
+            Depth::Basic
+        }
+    }
+}
+
+static DEPTH: once_cell::sync::Lazy<Depth> = once_cell::sync::Lazy::new(Depth::detect);
+
+/// Nearest index in the 6x6x6 colour cube or the grayscale ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube = |c: u8| match c {
+        0..=47 => 0,
+        48..=114 => 1,
+        c => (c - 35) / 40,
+    };
+    let (cr, cg, cb) = (cube(r), cube(g), cube(b));
+    let cube = 16 + 36 * cr + 6 * cg + cb;
+
+    let avg = (r as u16 + g as u16 + b as u16) / 3;
+    let gray = if avg > 238 {
+        23
+    } else {
+        avg.saturating_sub(3) as u8 / 10
+    };
+
+    let dist = |n: u8| {
+        let (x, y, z) = rgb(n);
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(x, r) + d(y, g) + d(z, b)
+    };
+
+    match dist(232 + gray) < dist(cube) {
+        true => 232 + gray,
+        false => cube,
+    }
+}
+
+/// Approximate RGB value of a 256-colour palette index.
+fn rgb(n: u8) -> (u8, u8, u8) {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (128, 0, 0),
+        (0, 128, 0),
+        (128, 128, 0),
+        (0, 0, 128),
+        (128, 0, 128),
+        (0, 128, 128),
+        (192, 192, 192),
+        (128, 128, 128),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (0, 0, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    match n {
+        0..=15 => BASIC[n as usize],
+        16..=231 => {
+            let level = |c: u8| if c == 0 { 0 } else { 55 + 40 * c };
+            let n = n - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let v = 8 + 10 * (n - 232);
+            (v, v, v)
+        }
+    }
+}
+
+/// Nearest of the 8 standard colours, as an offset from black.
+fn basic(r: u8, g: u8, b: u8) -> u8 {
+    let max = r.max(g).max(b) as u16;
+    if max < 64 {
+        return 0;
+    }
+
+    let bit = |c: u8| (c as u16 * 2 > max) as u8;
+    bit(r) | bit(g) << 1 | bit(b) << 2
+}
+
+impl std::str::FromStr for Colour {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "" => Ok(Colour::None),
+            "red" => Ok(Colour::Red),
+            "green" => Ok(Colour::Green),
+            "yellow" => Ok(Colour::Yellow),
+            "blue" => Ok(Colour::Blue),
+            "magenta" => Ok(Colour::Magenta),
+            "cyan" => Ok(Colour::Cyan),
+            "gradient" => Ok(Colour::Gradient),
+
+            hex if hex.starts_with('#') && hex.len() == 7 && hex.is_ascii() => {
+                let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+                match (channel(1), channel(3), channel(5)) {
+                    (Ok(r), Ok(g), Ok(b)) => Ok(Colour::Rgb(r, g, b)),
+                    _ => Err(anyhow::anyhow!("invalid hex colour {s:?}")),
+                }
+            }
+
+            _ => Err(anyhow::anyhow!("unknown colour {s:?}")),
+        }
+    }
 }
 
 impl Default for Colour {
@@ -43,32 +324,413 @@ impl Default for Colour {
     }
 }
 
-pub enum Style {
-    ASCII,
-    Block,
-    Balloon,
-    Pacman,
-    Custom(String),
+/// Colour plus text attributes of a printed segment.
+///
+///
+/// ## Examples
+/// ```
+/// use tqdm::style::{Colour, Ink};
+/// let ink = Ink::new(Colour::Cyan).bold();
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ink {
+    pub colour: Colour,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+}
+
+impl Ink {
+    pub fn new(colour: Colour) -> Self {
+        Ink {
+            colour,
+            ..Default::default()
+        }
+    }
+
+    pub fn bold(self) -> Self {
+        Ink { bold: true, ..self }
+    }
+
+    pub fn dim(self) -> Self {
+        Ink { dim: true, ..self }
+    }
+
+    pub fn italic(self) -> Self {
+        Ink {
+            italic: true,
+            ..self
+        }
+    }
+
+    /// Resolve `Gradient` colour at a given completion, see [Colour::at].
+    pub fn at(&self, pct: f64) -> Self {
+        Ink {
+            colour: self.colour.at(pct),
+            ..self.clone()
+        }
+    }
+
+    /// Escape sequence for attributes and colour, empty if plain.
+    pub fn ansi_code(&self) -> String {
+        let mut code = String::new();
+        if self.bold {
+            code.push_str("\x1b[1m");
+        }
+        if self.dim {
+            code.push_str("\x1b[2m");
+        }
+        if self.italic {
+            code.push_str("\x1b[3m");
+        }
+        code + &self.colour.ansi_code()
+    }
+
+    /// CSS declarations for attributes and colour, empty if plain.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// use tqdm::style::{Colour, Ink};
+    /// assert_eq!(Ink::new(Colour::Rgb(255, 128, 0)).bold().css(), "font-weight:bold;color:#ff8000;");
+    /// ```
+    ///
+    pub fn css(&self) -> String {
+        let mut css = String::new();
+        if self.bold {
+            css.push_str("font-weight:bold;");
+        }
+        if self.dim {
+            css.push_str("opacity:0.6;");
+        }
+        if self.italic {
+            css.push_str("font-style:italic;");
+        }
+        if let Some(colour) = self.colour.css() {
+            css.push_str(&format!("color:{colour};"));
+        }
+        css
+    }
+}
+
+impl From<Colour> for Ink {
+    fn from(colour: Colour) -> Self {
+        Ink::new(colour)
+    }
+}
+
+/// Inks of every part of a progress bar.
+///
+/// ```text
+/// desc: percentage|bar| counter [elapsed<eta, rate, postfix]
+/// ```
+///
+/// Once the bar finished, its body and outcome marker take `complete`,
+/// `warning` or `error` depending on the outcome, see [Outcome].
+///
+/// [Outcome]: crate::render::Outcome
+///
+///
+/// ## Examples
+/// ```
+/// use tqdm::style::{Colour, Ink, Theme};
+/// let theme = Theme {
+///     desc: Ink::new(Colour::Cyan).bold(),
+///     bar: Ink::new(Colour::Yellow),
+///     complete: Some(Ink::new(Colour::Green)),
+///     error: Some(Ink::new(Colour::Red)),
+///     ..Default::default()
+/// };
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Theme {
+    pub desc: Ink,
+    pub percentage: Ink,
+    pub bar: Ink,
+    pub counter: Ink,
+    pub elapsed: Ink,
+    pub eta: Ink,
+    pub rate: Ink,
+    pub postfix: Ink,
+
+    pub complete: Option<Ink>,
+    pub warning: Option<Ink>,
+    pub error: Option<Ink>,
+}
+
+/// Progress bar style.
+///
+/// A bar of `n` cells is drawn as `left`, completed cells of `fill`, one
+/// partially completed cell, the remaining columns tiled with `empty`, then
+/// `right`. The partial cell is `head` if any, or the glyph of `ramp`
+/// matching the fraction of that cell.
+///
+/// ```text
+/// |████████▋      |    [=======>      ]    ---C o  o  o
+/// ```
+///
+/// Presets are named after their former enum variants. `fill`, `head` and
+/// `ramp` must share the same display width, which defines a cell.
+///
+///
+/// ## Examples
+/// ```
+/// use tqdm::Style;
+/// let style = Style {
+///     fill: '#',
+///     head: Some('>'),
+///     left: "<".into(),
+///     right: ">".into(),
+///     ..Style::ASCII
+/// };
+/// assert_eq!(style.draw(0.5, 8), "####>   ");
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// Completed cells
+    pub fill: char,
+
+    /// Partial progress of the current cell, from empty to almost full
+    pub ramp: Cow<'static, str>,
+
+    /// Glyph leading the completed cells, replacing `ramp`
+    pub head: Option<char>,
+
+    /// Pattern tiled over remaining columns
+    pub empty: Cow<'static, str>,
+
+    /// Delimiters around the bar body
+    pub left: Cow<'static, str>,
+    pub right: Cow<'static, str>,
+}
+
+#[allow(non_upper_case_globals)]
+impl Style {
+    /// Pure ASCII bar with `"0123456789#"`
+    pub const ASCII: Style = Style {
+        fill: '#',
+        ramp: Cow::Borrowed("0123456789"),
+        head: None,
+        empty: Cow::Borrowed(" "),
+        left: Cow::Borrowed("|"),
+        right: Cow::Borrowed("|"),
+    };
+
+    /// Common bar with unicode characters `" ▏▎▍▌▋▊▉█"`
+    pub const Block: Style = Style {
+        fill: '█',
+        ramp: Cow::Borrowed(" ▏▎▍▌▋▊▉"),
+        ..Style::ASCII
+    };
+
+    /// Simulate balloon explosion with `".oO@*"`
+    pub const Balloon: Style = Style {
+        fill: '*',
+        ramp: Cow::Borrowed(".oO@"),
+        ..Style::ASCII
+    };
+
+    /// Inspired by Arch Linux ILoveCandy
+    pub const Pacman: Style = Style {
+        fill: '-',
+        ramp: Cow::Borrowed(""),
+        head: Some('C'),
+        empty: Cow::Borrowed(" o "),
+        ..Style::ASCII
+    };
+
+    /// Classic `[=====>    ]`
+    pub const Arrow: Style = Style {
+        fill: '=',
+        ramp: Cow::Borrowed(""),
+        head: Some('>'),
+        left: Cow::Borrowed("["),
+        right: Cow::Borrowed("]"),
+        ..Style::ASCII
+    };
+
+    /// Thin line like pip's download bar
+    pub const Pip: Style = Style {
+        fill: '━',
+        ramp: Cow::Borrowed(""),
+        head: Some('╸'),
+        left: Cow::Borrowed(" "),
+        right: Cow::Borrowed(""),
+        ..Style::ASCII
+    };
+
+    /// Create a style from a character ramp, whose last character fills
+    /// completed cells and the others show partial progress.
+    ///
+    ///
+    /// ## Examples
+    /// ```
+    /// use tqdm::Style;
+    /// let style = Style::custom(" .:#").unwrap();
+    /// assert_eq!(style.draw(0.5, 5), "##.  ");
+    /// ```
+    ///
+    pub fn custom(ramp: &str) -> Result<Style, StyleError> {
+        let mut chars = ramp.chars();
+        let fill = chars.next_back().ok_or(StyleError::Empty)?;
+
+        let style = Style {
+            fill,
+            ramp: Cow::Owned(chars.collect()),
+            ..Style::ASCII
+        };
+
+        style.validate()?;
+        Ok(style)
+    }
+
+    /// Check that the style can be drawn.
+    pub fn validate(&self) -> Result<(), StyleError> {
+        let glyphs = || {
+            iter::once(self.fill)
+                .chain(self.head)
+                .chain(self.ramp.chars())
+        };
+
+        let others = self
+            .empty
+            .chars()
+            .chain(self.left.chars())
+            .chain(self.right.chars());
+        if let Some(c) = glyphs().chain(others).find(|c| c.is_control()) {
+            return Err(StyleError::Control(c));
+        }
+
+        let width = self.fill.width().unwrap_or(0);
+        if let Some(c) = glyphs().find(|c| c.width().unwrap_or(0) != width || width == 0) {
+            return Err(StyleError::Width(c));
+        }
+
+        match self.empty.chars().find(|c| c.width().unwrap_or(0) == 0) {
+            Some(c) => Err(StyleError::Width(c)),
+            None => Ok(()),
+        }
+    }
+
+    /// Draw the bar body (without delimiters) at completion `pct` in
+    /// exactly `cols` columns.
+    pub fn draw(&self, pct: f64, cols: usize) -> String {
+        let width = self.fill.width().unwrap_or(1).max(1);
+        let cells = cols / width;
+
+        let pct = if pct.is_nan() {
+            0.0
+        } else {
+            pct.clamp(0.0, 1.0)
+        };
+        let progress = pct * cells as f64;
+        let full = (progress as usize).min(cells);
+
+        let mut bar: String = iter::repeat(self.fill).take(full).collect();
+        if full < cells {
+            let ramp: Vec<_> = self.ramp.chars().collect();
+            let partial = match self.head {
+                Some(head) => Some(head),
+                None if ramp.is_empty() => None,
+                None => {
+                    let frac = progress - full as f64;
+                    Some(ramp[((frac * ramp.len() as f64) as usize).min(ramp.len() - 1)])
+                }
+            };
+
+            if let Some(partial) = partial {
+                bar.push(partial);
+            }
+
+            // tile the empty pattern by columns, anchored to the start of the bar
+            let empty: Vec<_> = self.empty.chars().collect();
+            let mut col = bar.width();
+            while let Some(&c) = empty.get(col % empty.len().max(1)) {
+                let w = c.width().unwrap_or(1).max(1);
+                if col + w > cols {
+                    break;
+                }
+                bar.push(c);
+                col += w;
+            }
+        }
+
+        let padding = cols.saturating_sub(bar.width());
+        bar + &" ".repeat(padding)
+    }
 }
 
 impl Default for Style {
     fn default() -> Self {
-        Style::Block
+        match unicode() {
+            true => Style::Block,
+            false => Style::ASCII,
+        }
     }
 }
 
-impl std::fmt::Display for Style {
+/// Parse a preset name, or else a character ramp as [Style::custom].
+impl std::str::FromStr for Style {
+    type Err = StyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ascii" => Ok(Style::ASCII),
+            "block" => Ok(Style::Block),
+            "balloon" => Ok(Style::Balloon),
+            "pacman" => Ok(Style::Pacman),
+            "arrow" => Ok(Style::Arrow),
+            "pip" => Ok(Style::Pip),
+            _ => Style::custom(s),
+        }
+    }
+}
+
+/// Reason a [Style] cannot be drawn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StyleError {
+    /// No character at all
+    Empty,
+
+    /// Control characters would break the layout
+    Control(char),
+
+    /// Characters must share the same, non-zero display width
+    Width(char),
+}
+
+impl std::fmt::Display for StyleError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Style::ASCII => "0123456789#",
-                Style::Block => " ▏▎▍▌▋▊▉█",
-                Style::Balloon => ".oO@*",
-                Style::Pacman => "C-",
-                Style::Custom(n) => &n[..],
-            }
-        )
+        match self {
+            StyleError::Empty => write!(f, "style needs at least one character"),
+            StyleError::Control(c) => write!(f, "control character {c:?} in style"),
+            StyleError::Width(c) => write!(f, "character {c:?} has a different display width"),
+        }
     }
 }
+
+impl std::error::Error for StyleError {}
+
+/// Whether the locale advertises UTF-8, from `LC_ALL`, `LC_CTYPE` or `LANG`.
+///
+/// Always true on Windows, whose terminals render unicode regardless.
+pub fn unicode() -> bool {
+    *UNICODE
+}
+
+static UNICODE: once_cell::sync::Lazy<bool> = once_cell::sync::Lazy::new(|| {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.is_empty());
+
+    cfg!(windows) || locale.map_or(false, |locale| utf8(&locale))
+});
+
+pub(crate) fn utf8(locale: &str) -> bool {
+    let locale = locale.to_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
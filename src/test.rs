@@ -26,14 +26,12 @@ fn example_pbar() {
 
 #[test]
 #[ignore]
-
 fn very_slow() {
     tqdm(0..100).for_each(|_| thread::sleep(Duration::from_secs_f64(10.0)));
 }
 
 #[test]
 #[ignore]
-
 fn infinite() {
     for _ in tqdm(0..).desc(Some("infinite")) {
         thread::sleep(Duration::from_secs_f64(0.1));
@@ -70,6 +68,166 @@ fn shorter_total() {
     }
 }
 
+#[test]
+
+fn draw_style() {
+    assert_eq!(Style::Block.draw(0.5, 10), "█████     ");
+    assert_eq!(Style::Block.draw(0.55, 10), "█████▌    ");
+    assert_eq!(Style::ASCII.draw(0.37, 10), "###7      ");
+    assert_eq!(Style::Arrow.draw(0.5, 10), "=====>    ");
+    assert_eq!(Style::Arrow.draw(1.0, 10), "==========");
+    assert_eq!(Style::Pacman.draw(0.0, 10), "Co  o  o  ");
+    assert_eq!(Style::Pacman.draw(0.4, 10), "----C  o  ");
+
+    let wide = Style::custom("进度").unwrap();
+    assert_eq!(wide.draw(0.5, 9), "度度进   ");
+}
+
+#[test]
+
+fn narrow_width() {
+    let styles = [
+        Style::ASCII,
+        Style::Block,
+        Style::Balloon,
+        Style::Pacman,
+        Style::Arrow,
+        Style::Pip,
+    ];
+    for style in styles {
+        for width in 0..8 {
+            let mut pbar = pbar(Some(10)).style(style.clone()).width(Some(width));
+            for _ in 0..10 {
+                pbar.update(1).unwrap();
+                {
+                    let tqdm = BAR.lock().unwrap();
+                    let info = tqdm.get(&pbar.id).unwrap();
+                    info.format(SystemTime::now()).unwrap();
+                }
+            }
+        }
+    }
+}
+
+mod narrow {
+    use crate::render::{width, Classic, Render, Snapshot};
+    use crate::{Style, Theme};
+    use proptest::prelude::*;
+    use std::time::Duration;
+
+    fn styles() -> impl Strategy<Value = Style> {
+        prop_oneof![
+            Just(Style::ASCII),
+            Just(Style::Block),
+            Just(Style::Balloon),
+            Just(Style::Pacman),
+            Just(Style::Arrow),
+            Just(Style::Pip),
+            Just(Style::custom("进度").unwrap()),
+            Just(Style {
+                empty: "·:".into(),
+                ..Style::Arrow
+            }),
+        ]
+    }
+
+    fn pcts() -> impl Strategy<Value = f64> {
+        prop_oneof![
+            0.0..=1.0,
+            Just(0.0),
+            Just(1.0),
+            Just(f64::NAN),
+            Just(-1.0),
+            Just(2.0)
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn draw(style in styles(), pct in pcts(), cols in 0usize..200) {
+            let bar = style.draw(pct, cols);
+            prop_assert_eq!(width(&bar), cols);
+
+            let full = style.draw(1.0, cols);
+            prop_assert!(full.chars().filter(|&c| c != ' ').all(|c| c == style.fill));
+        }
+
+        #[test]
+        fn monotonic(style in styles(), a in 0.0..=1.0, b in 0.0..=1.0, cols in 0usize..200) {
+            let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+            let filled = |pct| style.draw(pct, cols).chars().filter(|&c| c == style.fill).count();
+            prop_assert!(filled(lo) <= filled(hi));
+        }
+
+        #[test]
+        fn classic(
+            style in styles(),
+            it in 0usize..=1000,
+            total in 1usize..=1000,
+            cols in 0usize..300,
+            desc in proptest::option::of("[a-z进度🚀 ]{0,40}"),
+        ) {
+            let theme = Theme::default();
+            let snapshot = Snapshot {
+                desc: desc.as_deref(),
+                postfix: None,
+                units: "it",
+                unit_scale: None,
+                style: &style,
+                theme: &theme,
+                outcome: None,
+                it,
+                its: Some(10.0),
+                total: Some(total),
+                elapsed: Duration::from_secs(3),
+            };
+
+            let line = |cols| -> String {
+                Classic.render(&snapshot, cols).into_iter().map(|segment| segment.text).collect()
+            };
+
+            // bars without a known total are not padded
+            let minimum = width(&line(0));
+            match it <= total {
+                true => prop_assert_eq!(width(&line(cols)), cols.max(minimum)),
+                false => prop_assert!(width(&line(cols)) <= cols.max(minimum)),
+            }
+        }
+    }
+}
+
+#[test]
+
+fn parse_style() {
+    use crate::style::StyleError;
+
+    assert_eq!("Block".parse(), Ok(Style::Block));
+    assert_eq!("pacman".parse(), Ok(Style::Pacman));
+    assert_eq!(" .:#".parse(), Style::custom(" .:#"));
+    assert_eq!(Style::custom(" .:#").unwrap().ramp, " .:");
+
+    assert_eq!("".parse::<Style>().unwrap_err(), StyleError::Empty);
+    assert_eq!(
+        ".\n#".parse::<Style>().unwrap_err(),
+        StyleError::Control('\n')
+    );
+    assert_eq!(".进".parse::<Style>().unwrap_err(), StyleError::Width('.'));
+    let invalid = Style {
+        head: Some('进'),
+        ..Style::Arrow
+    };
+    assert_eq!(invalid.validate(), Err(StyleError::Width('进')));
+
+    assert!(crate::style::utf8("en_US.UTF-8"));
+    assert!(crate::style::utf8("zh_CN.utf8"));
+    assert!(!crate::style::utf8("C"));
+    assert!(!crate::style::utf8("POSIX"));
+
+    for _ in tqdm(0..10).style(invalid) {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                  MULTI-BAR                                 */
 /* -------------------------------------------------------------------------- */
@@ -168,12 +326,925 @@ fn test_colours() {
         thread::sleep(Duration::from_millis(10));
     }
 
+    for _ in tqdm(0..10)
+        .desc(Some("Orange"))
+        .colour(Colour::Rgb(255, 165, 0))
+    {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    for _ in tqdm(0..10).desc(Some("Pink")).colour(Colour::Ansi256(218)) {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    for _ in tqdm(0..50).desc(Some("Gradient")).colour(Colour::Gradient) {
+        thread::sleep(Duration::from_millis(10));
+    }
+
     // Test that it still works without the colour config
     for _ in tqdm(0..10).desc(Some("No Colour Config")) {
         thread::sleep(Duration::from_millis(10));
     }
 }
 
+#[test]
+
+fn disable() {
+    let mut pbar = tqdm(0..100).desc(Some("disable")).disable(true);
+    assert!(BAR.lock().unwrap().get(&pbar.id).is_none());
+
+    pbar.update(10).unwrap();
+    pbar.set_desc(Some("still disabled"));
+    assert_eq!(pbar.step, 0);
+    assert_eq!(pbar.by_ref().count(), 100);
+
+    // updates never wait for the registry
+    let pbar = tqdm(0..100).disable(true);
+    let (locked, wait) = sync::mpsc::channel();
+    let holder = thread::spawn(move || {
+        let _tqdm = BAR.lock().unwrap();
+        locked.send(()).unwrap();
+        thread::sleep(Duration::from_secs(1));
+    });
+    wait.recv().unwrap();
+
+    let start = SystemTime::now();
+    assert_eq!(pbar.count(), 100);
+    assert!(start.elapsed().unwrap() < Duration::from_millis(500));
+    holder.join().unwrap();
+
+    // nor draw anything
+    let output = child("test::disable_child");
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty(), "{:?}", output.stderr);
+}
+
+#[test]
+#[ignore]
+fn disable_child() {
+    for _ in tqdm(0..3).desc(Some("disabled")).disable(true) {}
+
+    set_enabled(false);
+    for _ in tqdm(0..3).desc(Some("globally disabled")) {}
+}
+
+/// Run an ignored test in a process of its own, to inspect what it draws.
+fn child(test: &str) -> process::Output {
+    process::Command::new(env::current_exe().unwrap())
+        .args(["--ignored", "--exact", test])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+#[test]
+
+fn handlers() {
+    // the panic ends the process, so run it in a process of its own
+    let output = child("test::handlers_child");
+    assert!(!output.status.success());
+
+    // the bar is closed as failure, then the cursor shown again
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let closed = stderr.rfind(" handled: ").unwrap();
+    assert!(
+        stderr[..closed].ends_with(Outcome::Failure.marker()),
+        "{stderr}"
+    );
+    assert!(stderr[closed..].contains("\x1b[?25h"), "{stderr}");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("expected panic"), "{stdout}");
+    assert!(stdout.contains("recovered panic"), "{stdout}");
+
+    // the recovered panic only closed the bars of its thread
+    assert!(stderr.contains(" worker: "), "{stderr}");
+    assert_eq!(stderr.matches(" handled: ").count(), 1, "{stderr}");
+}
+
+#[test]
+#[ignore]
+fn handlers_child() {
+    // installing twice must not chain the hook twice
+    install_handlers();
+    install_handlers();
+
+    let path = env::temp_dir().join(format!("tqdm-{}-handlers.tsv", process::id()));
+    set_state_file(&path);
+
+    let mut pbar = pbar(Some(10)).desc(Some("handled"));
+    pbar.update(5).unwrap();
+
+    let worker = thread::spawn(|| {
+        let mut pbar = crate::pbar(Some(10)).desc(Some("worker"));
+        pbar.update(5).unwrap();
+
+        // never drawn, thus only saved when closed by the hook
+        let mut hidden = crate::pbar(Some(10))
+            .key("hidden")
+            .delay(Duration::from_secs(3600));
+        hidden.update(3).unwrap();
+        panic!("recovered panic");
+    });
+    assert!(worker.join().is_err());
+    assert!(BAR.lock().unwrap().contains_key(&pbar.id));
+    assert_eq!(state::load("hidden").map(|saved| saved.it), Some(3));
+    fs::remove_file(path).unwrap();
+
+    panic!("expected panic");
+}
+
+#[test]
+
+fn write_above() {
+    // bars are drawn on stderr, so write in a process of its own
+    let output = child("test::write_child");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    // cleared before the record, drawn again after it
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let (before, after) = stderr.split_once("logged\n").unwrap();
+    assert!(before.ends_with("\x1b[J"), "{stderr}");
+    assert!(after.contains(" written: "), "{stderr}");
+}
+
+#[test]
+#[ignore]
+fn write_child() {
+    let mut pbar = pbar(Some(10)).desc(Some("written"));
+    pbar.update(5).unwrap();
+    crate::write(&mut io::stderr(), b"logged\n").unwrap();
+}
+
+#[test]
+
+fn delay() {
+    let mut pbar = pbar(Some(100)).delay(Duration::from_millis(200));
+    pbar.update(1).unwrap();
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        assert!(!info.visible(info.t0));
+        assert!(info.visible(info.t0 + Duration::from_millis(200)));
+    }
+
+    // closed within its delay, a bar leaves no trace
+    let output = child("test::delay_child");
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("invisible"), "{stderr}");
+    assert!(stderr.contains("visible: 100%"), "{stderr}");
+}
+
+#[test]
+#[ignore]
+fn delay_child() {
+    for _ in tqdm(0..3)
+        .desc(Some("invisible"))
+        .delay(Duration::from_secs(10))
+    {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    for _ in tqdm(0..3).desc(Some("visible")) {}
+}
+
+#[test]
+
+fn colour_depth() {
+    use crate::style::Depth;
+
+    let orange = Colour::Rgb(255, 165, 0);
+    assert_eq!(orange.code(Depth::TrueColor), "\x1b[38;2;255;165;0m");
+    assert_eq!(orange.code(Depth::Ansi256), "\x1b[38;5;214m");
+    assert_eq!(orange.code(Depth::Basic), "\x1b[33m");
+
+    assert_eq!(
+        Colour::Rgb(128, 128, 128).code(Depth::Ansi256),
+        "\x1b[38;5;244m"
+    );
+    assert_eq!(Colour::Ansi256(196).code(Depth::Basic), "\x1b[31m");
+    assert_eq!(Colour::Ansi256(21).code(Depth::Basic), "\x1b[34m");
+
+    assert_eq!("#00ff00".parse::<Colour>().unwrap(), Colour::Rgb(0, 255, 0));
+    assert_eq!("Cyan".parse::<Colour>().unwrap(), Colour::Cyan);
+    assert!("#00ff0".parse::<Colour>().is_err());
+    assert!("#00gg00".parse::<Colour>().is_err());
+}
+
+#[test]
+
+fn theme() {
+    use crate::style::Ink;
+
+    let theme = Theme {
+        desc: Ink::new(Colour::Cyan).bold(),
+        counter: Ink::default().italic(),
+        bar: Ink::new(Colour::Yellow),
+        complete: Some(Ink::new(Colour::Green)),
+        error: Some(Ink::new(Colour::Red).dim()),
+        ..Default::default()
+    };
+
+    let mut pbar = pbar(Some(10))
+        .desc(Some("theme"))
+        .postfix(Some("loss=0.25"))
+        .theme(theme.clone())
+        .width(Some(80));
+    pbar.update(5).unwrap();
+
+    {
+        let mut tqdm = BAR.lock().unwrap();
+        let info = tqdm.get_mut(&pbar.id).unwrap();
+        let line = info.paint(info.t0, true).unwrap();
+        assert!(line.starts_with("\x1b[1m\x1b[36mtheme: \x1b[0m 50%|\x1b[33m"));
+        assert!(line.contains("| \x1b[3m5/10\x1b[0m ["));
+        assert!(line.ends_with(", ?it/s, loss=0.25]"));
+
+        info.outcome = Some(Outcome::Failure);
+        let line = info.paint(info.t0, true).unwrap();
+        assert!(line.contains("|\x1b[2m\x1b[31m"));
+    }
+
+    for i in tqdm(0..50).desc(Some("complete")).theme(theme.clone()) {
+        thread::sleep(Duration::from_millis(10));
+        if i == 25 {
+            pbar.set_postfix(Some(i));
+        }
+    }
+
+    for i in tqdm(0..50).desc(Some("error")).theme(theme) {
+        thread::sleep(Duration::from_millis(10));
+        if i == 25 {
+            break;
+        }
+    }
+}
+
+#[test]
+
+fn colour_policy() {
+    let detect = ColourPolicy::detect;
+
+    assert!(detect(None, None, Some("xterm-256color"), true));
+    assert!(!detect(None, None, Some("xterm-256color"), false));
+    assert!(!detect(None, None, Some("dumb"), true));
+    assert!(!detect(Some("1"), None, None, true));
+    assert!(detect(Some(""), None, None, true));
+    assert!(detect(None, Some("1"), Some("dumb"), false));
+    assert!(!detect(None, Some("0"), None, false));
+    assert!(!detect(Some("1"), Some("1"), None, true));
+
+    assert!(ColourPolicy::Always.enabled());
+    assert!(!ColourPolicy::Never.enabled());
+}
+
+#[test]
+
+fn outcome() {
+    let mut filtered = tqdm((0..100).filter(|i| i % 2 == 0)).desc(Some("filtered"));
+    assert_eq!(filtered.by_ref().count(), 50);
+    assert_eq!(filtered.outcome, Some(Outcome::Success));
+
+    for i in tqdm(0..100).desc(Some("broken")) {
+        if i == 50 {
+            break;
+        }
+    }
+
+    let mut pbar = pbar(Some(100)).desc(Some("warning"));
+    pbar.update(100).unwrap();
+    pbar.finish_with(Outcome::Warning).unwrap();
+
+    let mut pbar = crate::pbar(Some(100)).desc(Some("abandoned"));
+    pbar.update(30).unwrap();
+    pbar.abandon("connection lost").unwrap();
+
+    let panicked = thread::spawn(|| {
+        for i in tqdm(0..100).desc(Some("panicked")) {
+            if i == 50 {
+                panic!("expected panic");
+            }
+        }
+    });
+    assert!(panicked.join().is_err());
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                   RENDER                                   */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+
+fn custom_render() {
+    use crate::render::{Segment, Snapshot};
+
+    struct Fraction;
+
+    impl Render for Fraction {
+        fn render(&self, bar: &Snapshot, _width: usize) -> Vec<Segment> {
+            vec![
+                Segment::plain(bar.it),
+                Segment::new("/", Colour::Red),
+                Segment::plain(bar.total.unwrap_or(0)),
+            ]
+        }
+    }
+
+    let pbar = pbar(Some(10)).render(Fraction);
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        let line = info.paint(SystemTime::now(), true).unwrap();
+        assert_eq!(line, "0\x1b[31m/\x1b[0m10");
+    }
+
+    for _ in tqdm(0..100).render(Fraction) {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+
+fn panicking_render() {
+    use crate::render::{Segment, Snapshot};
+
+    struct Panic;
+
+    impl Render for Panic {
+        fn render(&self, _bar: &Snapshot, _width: usize) -> Vec<Segment> {
+            panic!("render")
+        }
+    }
+
+    let pbar = pbar(Some(10)).render(Panic);
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        assert!(info.paint(SystemTime::now(), false).is_err());
+    }
+
+    assert!(!BAR.is_poisoned());
+}
+
+#[test]
+
+fn json_lines() {
+    let mut pbar = pbar(Some(10)).desc(Some("say \"hi\"\n"));
+    pbar.update(4).unwrap();
+
+    let tqdm = BAR.lock().unwrap();
+    let info = tqdm.get(&pbar.id).unwrap();
+    let line = info.json("update", pbar.id, info.t0).unwrap();
+    assert_eq!(
+        line,
+        format!(
+            "{{\"event\":\"update\",\"id\":{},\"desc\":\"say \\\"hi\\\"\\n\",\"n\":4,\"total\":10,\"rate\":null,\"elapsed\":0,\"eta\":null,\"outcome\":null,\"units\":\"it\",\"postfix\":null}}",
+            pbar.id
+        )
+    );
+}
+
+#[test]
+
+fn remote_protocol() {
+    use crate::remote::{object, Value};
+
+    let mut pbar = pbar(Some(10)).desc(Some("say \"hi\"\n")).units("B");
+    pbar.update(4).unwrap();
+
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        let object = object(&info.json("update", pbar.id, info.t0).unwrap()).unwrap();
+
+        assert_eq!(object["event"], Value::String(String::from("update")));
+        assert_eq!(object["desc"], Value::String(String::from("say \"hi\"\n")));
+        assert_eq!(object["n"], Value::Number(4.));
+        assert_eq!(object["rate"], Value::Null);
+        assert_eq!(object["units"], Value::String(String::from("B")));
+    }
+
+    assert_eq!(object("{}").unwrap().len(), 0);
+    assert!(object("{\"a\":").is_none());
+    assert!(object("watch").is_none());
+}
+
+#[test]
+
+fn remote_serve() {
+    assert!(remote::serve("0.0.0.0:0").is_err());
+
+    // the port picked by the system is the one to connect to
+    let endpoint = remote::serve("127.0.0.1:0").unwrap();
+    assert!(!endpoint.ends_with(":0"), "{endpoint}");
+    net::TcpStream::connect(&endpoint).unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixStream;
+
+        let path = env::temp_dir().join(format!("tqdm-{}.sock", process::id()));
+        let endpoint = path.to_str().unwrap();
+        assert_eq!(remote::serve(endpoint).unwrap(), endpoint);
+        assert!(remote::endpoint().is_some());
+        assert!(env::var_os("TQDM_ENDPOINT").is_none());
+
+        let mut pbar = pbar(Some(10)).desc(Some("served"));
+        pbar.update(3).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, "{{\"event\":\"watch\"}}").unwrap();
+
+        // current state first, possibly after other tests' bars
+        let reader = BufReader::new(stream);
+        let line = reader
+            .lines()
+            .map(Result::unwrap)
+            .find(|line| line.contains("\"desc\":\"served\""))
+            .unwrap();
+        assert!(line.contains("\"n\":3"), "{line}");
+
+        fs::remove_file(path).unwrap();
+
+        // anything but a socket is left alone
+        let path = env::temp_dir().join(format!("tqdm-{}.txt", process::id()));
+        fs::write(&path, "important").unwrap();
+        assert!(remote::serve(path.to_str().unwrap()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "important");
+
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn remote_forward() {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+
+    let path = env::temp_dir().join(format!("tqdm-{}-forward.sock", process::id()));
+    remote::serve(path.to_str().unwrap()).unwrap();
+
+    let mut watcher = UnixStream::connect(&path).unwrap();
+    watcher
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    writeln!(watcher, "{{\"event\":\"watch\"}}").unwrap();
+
+    // this very binary plays the child process
+    let status = process::Command::new(env::current_exe().unwrap())
+        .args(["--ignored", "--exact", "test::remote_child"])
+        .env("TQDM_ENDPOINT", &path)
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let line = BufReader::new(watcher)
+        .lines()
+        .map(Result::unwrap)
+        .find(|line| {
+            line.contains("\"event\":\"close\"") && line.contains("\"desc\":\"forwarded\"")
+        })
+        .unwrap();
+    assert!(line.contains("\"n\":10"), "{line}");
+    assert!(line.contains("\"outcome\":\"success\""), "{line}");
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+#[ignore]
+fn remote_child() {
+    let mut pbar = pbar(Some(10)).desc(Some("forwarded"));
+    pbar.update(10).unwrap();
+}
+
+#[test]
+#[cfg(feature = "prometheus")]
+fn prometheus() {
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    let get = |addr, path| {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let mut pbar = pbar(Some(100)).desc(Some("say \"hi\""));
+    pbar.update(42).unwrap();
+
+    let addr = crate::prometheus::serve("127.0.0.1:0").unwrap();
+
+    let response = get(addr, "/metrics");
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    assert!(response.contains("# TYPE tqdm_n gauge"), "{response}");
+    let labels = format!("{{id=\"{}\",desc=\"say \\\"hi\\\"\"}}", pbar.id);
+    assert!(
+        response.contains(&format!("tqdm_n{labels} 42\n")),
+        "{response}"
+    );
+    assert!(
+        response.contains(&format!("tqdm_total{labels} 100\n")),
+        "{response}"
+    );
+
+    assert!(get(addr, "/").starts_with("HTTP/1.1 404"));
+}
+
+#[test]
+#[cfg(feature = "statsd")]
+fn statsd() {
+    use std::net::UdpSocket;
+
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    server
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    crate::statsd::push(server.local_addr().unwrap(), Duration::ZERO).unwrap();
+
+    // other tests push their bars too
+    let recv = |metric: &str| loop {
+        let mut buf = [0; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        let datagram = String::from_utf8_lossy(&buf[..n]).into_owned();
+        if datagram.contains(metric) {
+            break datagram;
+        }
+    };
+
+    let mut pbar = pbar(Some(100)).desc(Some("pushed bar"));
+    pbar.update(42).unwrap();
+    recv("tqdm.pushed_bar.n:42|g");
+
+    pbar.update(58).unwrap();
+    pbar.close().unwrap();
+    let datagram = recv("tqdm.pushed_bar.success");
+    assert!(datagram.contains("tqdm.pushed_bar.n:100|g"), "{datagram}");
+    assert!(
+        datagram.contains("tqdm.pushed_bar.success:1|c"),
+        "{datagram}"
+    );
+}
+
+#[test]
+#[cfg(feature = "dashboard")]
+fn dashboard() {
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpStream;
+
+    let get = |addr, path| {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        stream
+    };
+
+    let mut pbar = pbar(Some(100))
+        .desc(Some("<dashboard>"))
+        .width(Some(60))
+        .theme(Theme {
+            desc: style::Ink::new(Colour::Cyan).bold(),
+            ..Default::default()
+        });
+    pbar.update(42).unwrap();
+
+    let addr = crate::dashboard::serve("127.0.0.1:0").unwrap();
+
+    let mut page = String::new();
+    get(addr, "/").read_to_string(&mut page).unwrap();
+    assert!(page.contains("new EventSource(\"events\")"), "{page}");
+
+    let event = BufReader::new(get(addr, "/events"))
+        .lines()
+        .map(Result::unwrap)
+        .find(|line| line.starts_with("data: "))
+        .unwrap();
+    assert!(
+        event.contains("<span style=\"font-weight:bold;color:teal;\">&lt;dashboard&gt;: </span>"),
+        "{event}"
+    );
+    assert!(event.contains(" 42/100 ["), "{event}");
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                 ENVIRONMENT                                */
+/* -------------------------------------------------------------------------- */
+
+#[test]
+
+fn environment() {
+    // variables are read by every bar, so set them in a process of its own
+    let output = process::Command::new(env::current_exe().unwrap())
+        .args(["--ignored", "--exact", "test::environment_child"])
+        .env("TQDM_NCOLS", "80")
+        .env("TQDM_STYLE", " Balloon ")
+        .env("TQDM_LEAVE", "no")
+        .env("TQDM_DYNAMIC_NCOLS", "maybe")
+        .env("TQDM_MININTERVAL", "0.5")
+        .env("TQDM_MINITERS", "-1")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+#[ignore]
+fn environment_child() {
+    let config = crate::Config::default();
+    assert_eq!(config.width, Some(80));
+    assert_eq!(config.style, Style::Balloon);
+    assert!(config.clear);
+    assert!(!config.dynamic_ncols);
+
+    // unparseable values fall back to defaults
+    let pbar = pbar(Some(10));
+    assert_eq!(pbar.mininterval, Duration::from_secs_f64(0.5));
+    assert_eq!(pbar.miniters, 1);
+}
+
+#[test]
+
+fn unit_scale() {
+    use crate::render::{fsize, Classic};
+
+    assert_eq!(fsize(7.0, 1000.), "7.00");
+    assert_eq!(fsize(42.0, 1000.), "42.0");
+    assert_eq!(fsize(999.0, 1000.), "999");
+    assert_eq!(fsize(7618.0, 1000.), "7.62k");
+    assert_eq!(fsize(1048576.0, 1024.), "1.00M");
+
+    let mut pbar = pbar(Some(10000)).units("B").unit_scale(Some(1000));
+    pbar.update(7618).unwrap();
+
+    let tqdm = BAR.lock().unwrap();
+    let info = tqdm.get(&pbar.id).unwrap();
+    let line: String = Classic
+        .render(&info.snapshot(info.t0).unwrap(), 80)
+        .into_iter()
+        .map(|segment| segment.text)
+        .collect();
+    assert!(line.contains(" 7.62k/10.0k ["), "{line}");
+    drop(tqdm);
+
+    for divisor in [0, 1] {
+        let pbar = crate::pbar(Some(10000)).unit_scale(Some(divisor));
+        let tqdm = BAR.lock().unwrap();
+        assert_eq!(tqdm.get(&pbar.id).unwrap().config.unit_scale, None);
+    }
+}
+
+#[test]
+
+fn unicode_width() {
+    use crate::render::{width, Classic};
+
+    let mut pbar = pbar(Some(100))
+        .desc(Some("进度条 🚀 progress"))
+        .width(Some(60));
+    pbar.update(42).unwrap();
+
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        let snapshot = info.snapshot(info.t0).unwrap();
+
+        for cols in [60, 40] {
+            let line: String = Classic
+                .render(&snapshot, cols)
+                .into_iter()
+                .map(|segment| segment.text)
+                .collect();
+            assert_eq!(width(&line), cols, "{line}");
+        }
+
+        let line: String = Classic
+            .render(&snapshot, 50)
+            .into_iter()
+            .map(|segment| segment.text)
+            .collect();
+        assert!(line.starts_with("进度条 …: "), "{line}");
+    }
+
+    assert_eq!(render::fit("\x1b[31m进度\x1b[0m", 3), "\x1b[31m进\x1b[0m ");
+    assert_eq!(render::fit("abc", 5), "abc  ");
+}
+
+#[test]
+
+fn resize() {
+    let pbar = pbar(Some(100));
+    let dynamic = crate::pbar(Some(100)).dynamic_ncols(true);
+
+    {
+        let mut tqdm = BAR.lock().unwrap();
+        // pretend both were created in a 120-column terminal
+        for id in [pbar.id, dynamic.id] {
+            tqdm.get_mut(&id).unwrap().ncols = 120;
+        }
+
+        let width = |id| {
+            let info = tqdm.get(&id).unwrap();
+            render::width(&info.paint(info.t0, false).unwrap())
+        };
+        assert_eq!(width(pbar.id), 120);
+        assert_eq!(width(dynamic.id), size::<usize>().0);
+    }
+
+    assert_eq!(crate::lines(3, 24), 3);
+    assert_eq!(crate::lines(23, 24), 23);
+    assert_eq!(crate::lines(24, 24), 24);
+    assert_eq!(crate::lines(100, 24), 24);
+}
+
+#[test]
+
+fn resume() {
+    // the state file applies to every bar, so resume in a process of its own
+    let output = child("test::resume_child");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+#[ignore]
+fn resume_child() {
+    let path = env::temp_dir().join(format!("tqdm-{}.tsv", process::id()));
+    set_state_file(&path);
+
+    let mut pbar = pbar(Some(100)).key("resume\ttest");
+    pbar.update(40).unwrap();
+    pbar.abandon("interrupted").unwrap();
+
+    let mut pbar = crate::pbar(None).key("resume\ttest");
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        assert_eq!((info.it, info.total), (40, Some(100)));
+    }
+
+    pbar.update(60).unwrap();
+    pbar.close().unwrap();
+
+    // finished tasks start over
+    let pbar = crate::pbar(Some(100)).key("resume\ttest");
+    assert_eq!(BAR.lock().unwrap().get(&pbar.id).unwrap().it, 0);
+
+    let pbar = crate::pbar(Some(100)).initial(50);
+    assert_eq!(BAR.lock().unwrap().get(&pbar.id).unwrap().it, 50);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+
+fn history() {
+    // as would the history file, learn in a process of its own
+    let output = child("test::history_child");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+#[ignore]
+fn history_child() {
+    let path = env::temp_dir().join(format!("tqdm-{}-history.tsv", process::id()));
+    set_history_file(&path);
+
+    // unknown bars start without a rate
+    let mut pbar = pbar(Some(100)).desc(Some("history-test"));
+    {
+        let mut tqdm = BAR.lock().unwrap();
+        let info = tqdm.get_mut(&pbar.id).unwrap();
+        assert_eq!(info.its, None);
+
+        // pretend it ran for 10 seconds
+        info.t0 = info.t0.checked_sub(Duration::from_secs(10)).unwrap();
+    }
+    pbar.update(100).unwrap();
+    pbar.close().unwrap();
+
+    let pbar = crate::pbar(Some(100)).desc(Some("history-test"));
+    {
+        let tqdm = BAR.lock().unwrap();
+        let info = tqdm.get(&pbar.id).unwrap();
+        let its = info.its.unwrap();
+        assert!((9.9..=10.).contains(&its), "{its}");
+
+        let eta = info.snapshot(info.t0).unwrap().eta().unwrap();
+        assert_eq!(eta.as_secs_f64().round(), 10.);
+    }
+
+    // keys take precedence over descriptions
+    let pbar = crate::pbar(Some(100))
+        .desc(Some("history-test"))
+        .key("other");
+    assert_eq!(BAR.lock().unwrap().get(&pbar.id).unwrap().its, None);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+
+fn osc() {
+    let mut first = pbar(Some(200)).desc(Some("first\x07"));
+    let mut second = crate::pbar(None).desc(Some("second")).units("B");
+    first.update(50).unwrap();
+    second.update(7).unwrap();
+
+    let mut reporter = osc::Reporter {
+        osc: Osc::Primary,
+        last: None,
+    };
+
+    let mut report = |osc, ids: &[usize]| {
+        let tqdm = BAR.lock().unwrap();
+        let bars = ids.iter().map(|id| tqdm.get_key_value(id).unwrap());
+
+        let mut out = Vec::new();
+        reporter.osc = osc;
+        reporter.report(&mut out, bars, SystemTime::now()).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    // title saved once, and nothing written until progress changes
+    assert_eq!(
+        report(Osc::Primary, &[first.id, second.id]),
+        "\x1b[22;0t\x1b]0;first: 25%\x07\x1b]9;4;1;25\x07"
+    );
+    assert_eq!(report(Osc::Primary, &[first.id, second.id]), "");
+
+    assert_eq!(
+        report(Osc::Primary, &[second.id]),
+        "\x1b]0;second: 7B\x07\x1b]9;4;3;0\x07"
+    );
+    assert_eq!(
+        report(Osc::Aggregate, &[first.id, second.id]),
+        "\x1b]0;2 bars: 57it\x07\x1b]9;4;3;0\x07"
+    );
+
+    BAR.lock().unwrap().get_mut(&second.id).unwrap().total = Some(50);
+    assert_eq!(
+        report(Osc::Aggregate, &[first.id, second.id]),
+        "\x1b]0;2 bars: 22%\x07\x1b]9;4;1;22\x07"
+    );
+
+    // restored once all bars are gone
+    assert_eq!(report(Osc::Aggregate, &[]), "\x1b]9;4;0;0\x07\x1b[23;0t");
+    assert_eq!(report(Osc::Aggregate, &[]), "");
+
+    first.close().unwrap();
+    second.close().unwrap();
+}
+
+#[test]
+
+fn osc_pipe() {
+    // the report is global, so enable it in a process of its own
+    let output = process::Command::new(env::current_exe().unwrap())
+        .args(["--ignored", "--exact", "test::osc_child"])
+        .env("TQDM_OSC", "aggregate")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // stderr is a pipe here, not a terminal
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(" piped: "), "{stderr}");
+    assert!(!stderr.contains("\x1b]"), "{stderr}");
+}
+
+#[test]
+#[ignore]
+fn osc_child() {
+    let mut pbar = pbar(Some(10)).desc(Some("piped"));
+    pbar.update(5).unwrap();
+    pbar.close().unwrap();
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                ASYNCHRONOUS                                */
 /* -------------------------------------------------------------------------- */
@@ -206,4 +1277,8 @@ fn performance() {
     let start = SystemTime::now();
     for _i in tqdm(0..N) {}
     println!("w/ tqdm: {:.02}it/s", speed(start));
+
+    let start = SystemTime::now();
+    for _i in tqdm(0..N).disable(true) {}
+    println!("w/ disabled tqdm: {:.02}it/s", speed(start));
 }